@@ -1,12 +1,21 @@
-use std::{env::args, error::Error, fs::File, process};
+use std::{
+    env::args,
+    error::Error,
+    fs::File,
+    io::{self, IsTerminal},
+    process,
+};
 use tapehead::{
     self, PROGNAME,
-    repl::{self, FileMode},
-    strings::VERSION,
+    repl::{self, FileMode, Settings},
+    strings::{OPTIONS, VERSION},
 };
 
 pub fn usage() {
-    eprintln!("TapeHead v{}\n\nUsage: {} <file>", VERSION, *PROGNAME);
+    eprintln!(
+        "TapeHead v{}\n\nUsage: {} [options] <file>\n\n{}",
+        VERSION, *PROGNAME, OPTIONS
+    );
 }
 
 fn exit_with_error<T>(e: impl Error) -> T {
@@ -25,10 +34,45 @@ fn exit_with_usage<T>() -> T {
 }
 
 fn main() {
-    let file_path = args().nth(1).unwrap_or_else(exit_with_usage);
+    let mut settings = Settings::default();
+    let mut file_path = None;
+    let mut color = None;
+    let mut options_done = false;
+
+    let mut args = args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            // Anything after `--` is the path, even if it starts with `-`.
+            _ if options_done => match file_path {
+                Some(_) => exit_with_usage(),
+                None => file_path = Some(arg),
+            },
+            "--" => options_done = true,
+            "-s" | "--size" => settings.show_size = true,
+            "--color" => color = parse_color(&args.next().unwrap_or_else(exit_with_usage)),
+            _ if arg.starts_with("--color=") => color = parse_color(&arg["--color=".len()..]),
+            _ if arg.starts_with('-') || file_path.is_some() => exit_with_usage(),
+            _ => file_path = Some(arg),
+        }
+    }
+
+    settings.hexdump.color = color.unwrap_or_else(|| io::stdout().is_terminal());
+    settings.escape_reads = io::stdout().is_terminal();
+
+    let file_path = file_path.unwrap_or_else(exit_with_usage);
     let (file, file_mode) = try_open(&file_path).unwrap_or_else(exit_with_error);
 
-    repl::run(&file_path, file, file_mode).unwrap_or_else(exit_with_error);
+    repl::run(&file_path, file, file_mode, settings).unwrap_or_else(exit_with_error);
+}
+
+/// Parses the value of `--color`. `None` means colour if stdout is a terminal.
+fn parse_color(when: &str) -> Option<bool> {
+    match when {
+        "auto" => None,
+        "always" => Some(true),
+        "never" => Some(false),
+        _ => exit_with_usage(),
+    }
 }
 
 fn try_open(file_path: &String) -> std::io::Result<(File, FileMode)> {
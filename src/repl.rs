@@ -1,4 +1,16 @@
+mod charset;
+mod device;
+mod display;
+mod encoding;
+mod export;
+mod hexdump;
+mod inspect;
 mod parser;
+mod records;
+mod settings;
+mod template;
+mod value;
+mod varint;
 
 use std::{
     error::Error,
@@ -6,7 +18,20 @@ use std::{
     io::{self, BufRead, Read, Seek, SeekFrom, Write},
 };
 
-use crate::{repl::parser::Command, strings};
+pub use settings::Settings;
+
+use crate::{
+    repl::{
+        parser::{
+            BitsCommand, Command, Range, SetCommand, TemplateCommand, TransformCommand, TransformOp,
+        },
+        template::Templates,
+    },
+    strings,
+};
+
+/// Bytes read or written at a time by commands that work through a range.
+const CHUNK_SIZE: u64 = 64 * 1024;
 
 #[derive(Debug)]
 pub enum FileMode {
@@ -19,19 +44,23 @@ fn prologue() {
     eprintln!("{}", *strings::PROLOGUE);
 }
 
-pub fn run(path: &String, mut file: File, file_mode: FileMode) -> io::Result<()> {
+pub fn run(
+    path: &String,
+    mut file: File,
+    file_mode: FileMode,
+    mut settings: Settings,
+) -> io::Result<()> {
     use Command::*;
 
-    let size = file.metadata()?.len();
-    let unit = if size == 1 { "byte" } else { "bytes" };
-
     prologue();
 
-    eprintln!("File: \"{path}\" ({size} {unit}) [{file_mode:?}]\n");
+    print_file_info(path, &file, &file_mode)?;
+    eprintln!();
 
     let mut buffer = Vec::<u8>::with_capacity(8192);
     let mut read_count = 0usize;
     let mut write_count = 0usize;
+    let mut templates = Templates::default();
 
     loop {
         let pos = try_get_pos(&file);
@@ -46,8 +75,16 @@ pub fn run(path: &String, mut file: File, file_mode: FileMode) -> io::Result<()>
         } else {
             String::new()
         };
+        let size_str = if settings.show_size {
+            format!(
+                ", size:{}",
+                file_size(&file).map_or("*".into(), |s| s.to_string())
+            )
+        } else {
+            String::new()
+        };
 
-        eprint!("[{in_str}{out_str}{pos_str}]> ");
+        eprint!("[{in_str}{out_str}{pos_str}{size_str}]> ");
         io::stderr().flush()?;
 
         buffer.clear();
@@ -81,11 +118,6 @@ pub fn run(path: &String, mut file: File, file_mode: FileMode) -> io::Result<()>
             Nop => continue,
             Quit => break,
             Help => help(),
-            Seek(cmd) => {
-                if let Err(e) = try_seek(&file, cmd.0) {
-                    error(e);
-                }
-            }
             Read(cmd) => {
                 match try_seek(&file, cmd.seek)
                     .and_then(|_| read_to_buffer(&mut file, &mut buffer, cmd.count))
@@ -98,7 +130,13 @@ pub fn run(path: &String, mut file: File, file_mode: FileMode) -> io::Result<()>
                 }
 
                 // Print contents.
-                io::stdout().write_all(&buffer).unwrap_or_else(error);
+                if settings.escape_reads && !cmd.raw {
+                    io::stdout()
+                        .write_all(&display::escape_control(&buffer))
+                        .unwrap_or_else(error);
+                } else {
+                    io::stdout().write_all(&buffer).unwrap_or_else(error);
+                }
                 io::stdout().flush()?;
                 if read_count > 0 {
                     // Prompt on new line.
@@ -119,8 +157,124 @@ pub fn run(path: &String, mut file: File, file_mode: FileMode) -> io::Result<()>
                     Ok(count) => read_count = count,
                 }
 
+                let mut layout = settings.hexdump.clone();
+                for option in cmd.options {
+                    layout.apply(option);
+                }
+
                 // Print hexdump
-                print_hexdump(start_pos, &buffer).unwrap_or_else(error);
+                print_hexdump(start_pos, &buffer, &layout).unwrap_or_else(error);
+            }
+            Readi(cmd) => {
+                let width = cmd.value_type.width;
+
+                match try_seek(&file, cmd.seek)
+                    .and_then(|_| read_to_buffer(&mut file, &mut buffer, Some(width)))
+                {
+                    Err(e) => {
+                        error(e);
+                        continue;
+                    }
+                    Ok(count) => read_count = count,
+                }
+
+                if read_count < width {
+                    error(strings::UNEXPECTED_EOF);
+                    continue;
+                }
+
+                println!("{}", cmd.value_type.format(&buffer));
+            }
+            Readv(cmd) => {
+                match try_seek(&file, cmd.seek).and_then(|_| read_varint(&file, &mut buffer)) {
+                    Err(e) => {
+                        error(e);
+                        continue;
+                    }
+                    Ok(count) => read_count = count,
+                }
+
+                match cmd.format.format(&buffer) {
+                    Some((value, len)) => {
+                        let unit = if len == 1 { "byte" } else { "bytes" };
+                        println!("{value} ({len} {unit})");
+                    }
+                    None => error(strings::MALFORMED_VARINT),
+                }
+            }
+            Readbits(cmd) => {
+                let mut start_pos: Option<u64> = None;
+
+                match try_seek(&file, cmd.seek).and_then(|new_pos| {
+                    start_pos = new_pos;
+                    read_to_buffer(&mut file, &mut buffer, Some(cmd.count))
+                }) {
+                    Err(e) => {
+                        error(e);
+                        continue;
+                    }
+                    Ok(count) => read_count = count,
+                }
+
+                if !buffer.is_empty() {
+                    let bits = display::format_bits(start_pos.unwrap_or(0), &buffer, cmd.order);
+                    print!("{bits}");
+                }
+            }
+            Reads(cmd) => {
+                match try_seek(&file, cmd.seek)
+                    .and_then(|_| read_c_string(&file, &mut buffer, cmd.max))
+                {
+                    Err(e) => {
+                        error(e);
+                        continue;
+                    }
+                    Ok(count) => read_count = count,
+                }
+
+                let text = buffer.strip_suffix(b"\0").unwrap_or(&buffer);
+                println!("{}", display::quote(text));
+            }
+            Readp(cmd) => {
+                let width = cmd.prefix.width;
+
+                match try_seek(&file, cmd.seek)
+                    .and_then(|_| read_to_buffer(&mut file, &mut buffer, Some(width)))
+                {
+                    Err(e) => {
+                        error(e);
+                        continue;
+                    }
+                    Ok(count) => read_count = count,
+                }
+
+                if read_count < width {
+                    error(strings::UNEXPECTED_EOF);
+                    continue;
+                }
+
+                let Ok(len) = usize::try_from(cmd.prefix.raw(&buffer)) else {
+                    error(strings::LENGTH_TOO_LARGE);
+                    continue;
+                };
+
+                // The length comes from the file, so grow the buffer as bytes
+                // arrive rather than allocating it all up front.
+                buffer.clear();
+                match (&file).take(len as u64).read_to_end(&mut buffer) {
+                    Err(e) => {
+                        error(e);
+                        continue;
+                    }
+                    Ok(count) => read_count += count,
+                }
+
+                if buffer.len() < len {
+                    error(strings::UNEXPECTED_EOF);
+                    continue;
+                }
+
+                println!("{}", display::quote(&buffer));
             }
             Write(cmd) => {
                 let write_buf = &buffer[cmd.index..];
@@ -138,6 +292,257 @@ pub fn run(path: &String, mut file: File, file_mode: FileMode) -> io::Result<()>
                 Err(e) => error(e),
                 Ok(()) => write_count = cmd.bytes.len(),
             },
+            Writeenc(cmd) => {
+                match try_seek(&file, cmd.seek).and_then(|_| file.write_all(&cmd.bytes)) {
+                    Err(e) => error(e),
+                    Ok(()) => write_count = cmd.bytes.len(),
+                }
+            }
+            WriteencFile(cmd) => {
+                let bytes = match std::fs::read(&cmd.path) {
+                    Err(e) => {
+                        error(format!("{}: {e}", cmd.path));
+                        continue;
+                    }
+                    Ok(text) => match cmd.encoding.decode(&text) {
+                        Err(e) => {
+                            error(format!("{}: {e}", cmd.path));
+                            continue;
+                        }
+                        Ok(bytes) => bytes,
+                    },
+                };
+
+                match try_seek(&file, cmd.seek).and_then(|_| file.write_all(&bytes)) {
+                    Err(e) => error(e),
+                    Ok(()) => write_count = bytes.len(),
+                }
+            }
+            Writei(cmd) => match try_seek(&file, cmd.seek).and_then(|_| file.write_all(&cmd.bytes))
+            {
+                Err(e) => error(e),
+                Ok(()) => write_count = cmd.bytes.len(),
+            },
+            Writev(cmd) => match try_seek(&file, cmd.seek).and_then(|_| file.write_all(&cmd.bytes))
+            {
+                Err(e) => error(e),
+                Ok(()) => write_count = cmd.bytes.len(),
+            },
+            Setbits(cmd) => match apply_mask(&file, &cmd, |byte, mask| byte | mask) {
+                Err(e) => error(e),
+                Ok(count) => write_count = count as usize,
+            },
+            Clearbits(cmd) => match apply_mask(&file, &cmd, |byte, mask| byte & !mask) {
+                Err(e) => error(e),
+                Ok(count) => write_count = count as usize,
+            },
+            Togglebits(cmd) => match apply_mask(&file, &cmd, |byte, mask| byte ^ mask) {
+                Err(e) => error(e),
+                Ok(count) => write_count = count as usize,
+            },
+            Transform(cmd) => match transform(&file, &cmd) {
+                Err(e) => error(e),
+                Ok(count) => write_count = count as usize,
+            },
+            Swap(cmd) => match swap(&file, &cmd.range, cmd.width) {
+                Err(e) => error(e),
+                Ok(count) => write_count = count as usize,
+            },
+            Reverse(cmd) => match reverse(&file, &cmd.0) {
+                Err(e) => error(e),
+                Ok(count) => write_count = count as usize,
+            },
+            Seek(cmd) => {
+                if let Err(e) = try_seek(&file, cmd.0) {
+                    error(e);
+                }
+            }
+            Fill(cmd) => match fill(&file, &cmd.range, &cmd.pattern) {
+                Err(e) => error(e),
+                Ok(count) => write_count = count as usize,
+            },
+            Fillb(cmd) => match fill(&file, &cmd.range, &cmd.pattern) {
+                Err(e) => error(e),
+                Ok(count) => write_count = count as usize,
+            },
+            Insert(cmd) => {
+                let insert_buf = &buffer[cmd.index..];
+                if insert_buf.is_empty() {
+                    continue;
+                }
+
+                match insert(&file, cmd.seek, insert_buf) {
+                    Err(e) => error(e),
+                    Ok(()) => write_count = insert_buf.len(),
+                }
+            }
+            Insertb(cmd) => match insert(&file, cmd.seek, &cmd.bytes) {
+                Err(e) => error(e),
+                Ok(()) => write_count = cmd.bytes.len(),
+            },
+            Delete(cmd) => delete(&file, &cmd.0).unwrap_or_else(error),
+            Truncate(cmd) => match resolve_seek(&file, cmd.0).and_then(|size| file.set_len(size)) {
+                Err(e) => error(e),
+                Ok(()) => print_file_info(path, &file, &file_mode).unwrap_or_else(error),
+            },
+
+            Dump(cmd) => {
+                let mut start_pos: Option<u64> = None;
+
+                match try_seek(&file, cmd.range.seek).and_then(|new_pos| {
+                    start_pos = new_pos;
+                    let count = cmd
+                        .range
+                        .count
+                        .map(usize::try_from)
+                        .transpose()
+                        .map_err(|_| io::Error::other(strings::SIZE_OUT_OF_RANGE))?;
+                    read_to_buffer(&mut file, &mut buffer, count)
+                }) {
+                    Err(e) => {
+                        error(e);
+                        continue;
+                    }
+                    Ok(count) => read_count = count,
+                }
+
+                match cmd.format.format(start_pos.unwrap_or(0), &buffer) {
+                    Err(e) => error(e),
+                    Ok(output) => print!("{output}"),
+                }
+            }
+            Load(cmd) => {
+                let chunks = match std::fs::read(&cmd.path) {
+                    Err(e) => {
+                        error(format!("{}: {e}", cmd.path));
+                        continue;
+                    }
+                    Ok(text) => match cmd.format.parse(&text) {
+                        Err(e) => {
+                            error(format!("{}: {e}", cmd.path));
+                            continue;
+                        }
+                        Ok(chunks) => chunks,
+                    },
+                };
+
+                match load_chunks(&file, &chunks, cmd.base) {
+                    Err(e) => error(e),
+                    Ok(count) => write_count = count,
+                }
+            }
+            Inspect(cmd) => {
+                let mut start_pos: Option<u64> = None;
+
+                match try_seek(&file, cmd.0).and_then(|new_pos| {
+                    start_pos = new_pos;
+                    read_to_buffer(&mut file, &mut buffer, Some(inspect::MAX_LEN))
+                }) {
+                    Err(e) => {
+                        error(e);
+                        continue;
+                    }
+                    Ok(count) => read_count = count,
+                }
+
+                // Leave the file pointer at the inspected position.
+                if let Some(start_pos) = start_pos
+                    && let Err(e) = file.seek(SeekFrom::Start(start_pos))
+                {
+                    error(e);
+                    continue;
+                }
+
+                print!("{}", inspect::inspect(&buffer));
+            }
+            Template(TemplateCommand::Load(template_path)) => {
+                match std::fs::read_to_string(&template_path) {
+                    Err(e) => error(format!("{template_path}: {e}")),
+                    Ok(text) => match templates.load(&text) {
+                        Err(e) => error(format!("{template_path}: {e}")),
+                        Ok(names) => eprintln!("Loaded: {}", names.join(", ")),
+                    },
+                }
+            }
+            Template(TemplateCommand::List) => {
+                for st in templates.visible() {
+                    let unit = if st.size == 1 { "byte" } else { "bytes" };
+                    eprintln!("{} ({} {unit})", st.name, st.size);
+                }
+            }
+            Decode(cmd) => {
+                let Some(st) = templates.get(&cmd.name) else {
+                    error(strings::UNKNOWN_STRUCT);
+                    continue;
+                };
+                let mut start_pos: Option<u64> = None;
+
+                match try_seek(&file, cmd.seek).and_then(|new_pos| {
+                    start_pos = new_pos;
+                    read_to_buffer(&mut file, &mut buffer, Some(st.size))
+                }) {
+                    Err(e) => {
+                        error(e);
+                        continue;
+                    }
+                    Ok(count) => read_count = count,
+                }
+
+                if read_count < st.size {
+                    error(strings::UNEXPECTED_EOF);
+                    continue;
+                }
+
+                let fields = templates.fields(st);
+                print!(
+                    "{}",
+                    template::format_struct(&fields, &buffer, start_pos.unwrap_or(0))
+                );
+            }
+            DecodeArray(cmd) => {
+                let Some(st) = templates.get(&cmd.name) else {
+                    error(strings::UNKNOWN_STRUCT);
+                    continue;
+                };
+                let Some(size) = st.size.checked_mul(cmd.count) else {
+                    error(strings::INVALID_DIGIT_IN_COUNT_ARG);
+                    continue;
+                };
+                let mut start_pos: Option<u64> = None;
+
+                match try_seek(&file, cmd.seek).and_then(|new_pos| {
+                    start_pos = new_pos;
+                    read_to_buffer(&mut file, &mut buffer, Some(size))
+                }) {
+                    Err(e) => {
+                        error(e);
+                        continue;
+                    }
+                    Ok(count) => read_count = count,
+                }
+
+                if read_count < size {
+                    error(strings::UNEXPECTED_EOF);
+                    continue;
+                }
+
+                let fields = templates.fields(st);
+                print!(
+                    "{}",
+                    template::format_array(&fields, st.size, &buffer, start_pos.unwrap_or(0))
+                );
+            }
+            Set(cmd) => match set_field(&file, &templates, &cmd) {
+                Err(e) => error(e),
+                Ok(count) => write_count = count,
+            },
+            Config(None) => {
+                for (name, value) in settings.entries() {
+                    eprintln!("{name:<12} {value}");
+                }
+            }
+            Config(Some(setting)) => settings.apply(setting),
+            Info => print_info(path, &file, &file_mode).unwrap_or_else(error),
         }
     }
 
@@ -146,11 +551,102 @@ pub fn run(path: &String, mut file: File, file_mode: FileMode) -> io::Result<()>
     Ok(())
 }
 
+fn print_file_info(path: &String, file: &File, file_mode: &FileMode) -> io::Result<()> {
+    let size = file_size(file)?;
+    let unit = if size == 1 { "byte" } else { "bytes" };
+
+    eprintln!("File: \"{path}\" ({size} {unit}) [{file_mode:?}]");
+
+    Ok(())
+}
+
+fn print_info(path: &String, file: &File, file_mode: &FileMode) -> io::Result<()> {
+    let metadata = file.metadata()?;
+    let size = file_size(file)?;
+    let unit = if size == 1 { "byte" } else { "bytes" };
+
+    eprintln!("File:     \"{path}\"");
+    eprintln!("Size:     {size} {unit}");
+    eprintln!("Type:     {}", file_type_name(&metadata.file_type()));
+    eprintln!("Mode:     {file_mode:?}");
+
+    if let Some(geometry) = device::block_device_geometry(file)? {
+        eprintln!(
+            "Sectors:  {} bytes logical, {} bytes physical",
+            geometry.logical_sector_size, geometry.physical_sector_size
+        );
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        let perms = metadata.mode() & 0o7777;
+        let rwx: String = (0..9)
+            .rev()
+            .map(|bit| match perms & (1 << bit) {
+                0 => '-',
+                _ => ['x', 'w', 'r'][bit % 3],
+            })
+            .collect();
+
+        eprintln!("Inode:    {}", metadata.ino());
+        eprintln!("Perms:    {perms:04o} ({rwx})");
+        eprintln!("Accessed: {}", display::format_unix_time(metadata.atime()));
+        eprintln!("Modified: {}", display::format_unix_time(metadata.mtime()));
+        eprintln!("Changed:  {}", display::format_unix_time(metadata.ctime()));
+    }
+
+    Ok(())
+}
+
+fn file_type_name(file_type: &std::fs::FileType) -> &'static str {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+
+        if file_type.is_fifo() {
+            return "FIFO";
+        } else if file_type.is_char_device() {
+            return "character device";
+        } else if file_type.is_block_device() {
+            return "block device";
+        } else if file_type.is_socket() {
+            return "socket";
+        }
+    }
+
+    if file_type.is_file() {
+        "regular file"
+    } else if file_type.is_dir() {
+        "directory"
+    } else if file_type.is_symlink() {
+        "symbolic link"
+    } else {
+        "unknown"
+    }
+}
+
+fn file_size(file: &File) -> io::Result<u64> {
+    match device::block_device_geometry(file)? {
+        Some(geometry) => Ok(geometry.size),
+        None => Ok(file.metadata()?.len()),
+    }
+}
+
 fn try_get_pos(mut file: &File) -> Option<u64> {
     file.stream_position().ok()
 }
 
-fn try_seek(mut file: &File, seek: SeekFrom) -> io::Result<Option<u64>> {
+fn try_seek(mut file: &File, mut seek: SeekFrom) -> io::Result<Option<u64>> {
+    // Block devices report a size of 0, so resolve the position ourselves.
+    if let SeekFrom::End(n) = seek
+        && let Ok(Some(geometry)) = device::block_device_geometry(file)
+        && let Some(pos) = geometry.size.checked_add_signed(n)
+    {
+        seek = SeekFrom::Start(pos);
+    }
+
     if seek != SeekFrom::Current(0) {
         return match file.seek(seek) {
             Ok(new_pos) => Ok(Some(new_pos)),
@@ -164,6 +660,380 @@ fn try_seek(mut file: &File, seek: SeekFrom) -> io::Result<Option<u64>> {
     }
 }
 
+/// Returns the position `seek` refers to, without moving the file pointer.
+fn resolve_seek(file: &File, seek: SeekFrom) -> io::Result<u64> {
+    let pos = match seek {
+        SeekFrom::Start(n) => Some(n),
+        SeekFrom::Current(n) => try_get_pos(file)
+            .ok_or(io::Error::other(strings::NOT_SEEKABLE_USE_DOT))?
+            .checked_add_signed(n),
+        SeekFrom::End(n) => file_size(file)?.checked_add_signed(n),
+    };
+
+    pos.ok_or(io::Error::other(strings::SIZE_OUT_OF_RANGE))
+}
+
+/// Seeks to the start of `range` and returns the number of bytes it spans.
+fn seek_range(file: &File, range: &Range) -> io::Result<u64> {
+    let start_pos = try_seek(file, range.seek)?;
+
+    if let Some(count) = range.count {
+        return Ok(count);
+    }
+    // No count. Span until end.
+
+    let Some(start_pos) = start_pos else {
+        return Err(io::Error::other(strings::NOT_SEEKABLE_GIVE_COUNT));
+    };
+    let size = file_size(file)?;
+
+    Ok(size.saturating_sub(start_pos))
+}
+
+/// Writes the value of a structure field, given the position of the
+/// structure.
+fn set_field(mut file: &File, templates: &Templates, cmd: &SetCommand) -> io::Result<usize> {
+    let st = templates
+        .get(&cmd.name)
+        .ok_or(io::Error::other(strings::UNKNOWN_STRUCT))?;
+    let field = templates
+        .field(st, &cmd.field)
+        .ok_or(io::Error::other(strings::UNKNOWN_FIELD))?;
+    let bytes = field.kind.encode(&cmd.value).map_err(io::Error::other)?;
+
+    let offset = field.offset as i64;
+    let seek = match cmd.seek {
+        SeekFrom::Start(n) => n.checked_add(offset as u64).map(SeekFrom::Start),
+        SeekFrom::Current(n) => n.checked_add(offset).map(SeekFrom::Current),
+        SeekFrom::End(n) => n.checked_add(offset).map(SeekFrom::End),
+    };
+    let seek = seek.ok_or(io::Error::other(strings::INVALID_SEEK_ARG))?;
+
+    try_seek(file, seek)?;
+    file.write_all(&bytes)?;
+
+    Ok(bytes.len())
+}
+
+/// Combines the bytes at the command's position with its mask using `op`.
+fn apply_mask(file: &File, cmd: &BitsCommand, op: impl Fn(u8, u8) -> u8) -> io::Result<u64> {
+    try_seek(file, cmd.seek)?;
+    check_available(file, cmd.mask.len() as u64)?;
+
+    modify_in_place(file, cmd.mask.len() as u64, |chunk, offset| {
+        for (byte, mask) in chunk.iter_mut().zip(&cmd.mask[offset as usize..]) {
+            *byte = op(*byte, *mask);
+        }
+    })
+}
+
+/// Applies the command's operation to each byte in its range, with the key
+/// repeated across the range.
+fn transform(file: &File, cmd: &TransformCommand) -> io::Result<u64> {
+    let count = seek_range(file, &cmd.range)?;
+    check_available(file, count)?;
+
+    modify_in_place(file, count, |chunk, offset| {
+        for (i, byte) in chunk.iter_mut().enumerate() {
+            let key = match cmd.key.len() {
+                0 => 0,
+                len => cmd.key[((offset + i as u64) % len as u64) as usize],
+            };
+            *byte = match cmd.op {
+                TransformOp::Xor => *byte ^ key,
+                TransformOp::And => *byte & key,
+                TransformOp::Or => *byte | key,
+                TransformOp::Not => !*byte,
+                TransformOp::Add => byte.wrapping_add(key),
+                TransformOp::Sub => byte.wrapping_sub(key),
+                TransformOp::Rol => byte.rotate_left(key as u32),
+                TransformOp::Ror => byte.rotate_right(key as u32),
+            };
+        }
+    })
+}
+
+/// Reverses the byte order of each `width`-byte word in `range`.
+fn swap(file: &File, range: &Range, width: usize) -> io::Result<u64> {
+    let count = seek_range(file, range)?;
+    if count % width as u64 != 0 {
+        return Err(io::Error::other(strings::RANGE_NOT_MULTIPLE_OF_WIDTH));
+    }
+    check_available(file, count)?;
+
+    // Chunks hold whole words, since the chunk size is a multiple of 8.
+    modify_in_place(file, count, |chunk, _| {
+        chunk.chunks_exact_mut(width).for_each(<[u8]>::reverse);
+    })
+}
+
+/// Reverses the order of the bytes in `range`, swapping chunks from either
+/// end.
+fn reverse(mut file: &File, range: &Range) -> io::Result<u64> {
+    let count = seek_range(file, range)?;
+    let start_pos = check_available(file, count)?;
+
+    let chunk_size = CHUNK_SIZE.min(count / 2) as usize;
+    let mut head = vec![0u8; chunk_size];
+    let mut tail = vec![0u8; chunk_size];
+
+    let (mut low, mut high) = (start_pos, start_pos + count);
+    while high - low >= 2 {
+        let n = CHUNK_SIZE.min((high - low) / 2);
+        let head = &mut head[..n as usize];
+        let tail = &mut tail[..n as usize];
+
+        file.seek(SeekFrom::Start(low))?;
+        file.read_exact(head)?;
+        file.seek(SeekFrom::Start(high - n))?;
+        file.read_exact(tail)?;
+
+        head.reverse();
+        tail.reverse();
+
+        file.seek(SeekFrom::Start(low))?;
+        file.write_all(tail)?;
+        file.seek(SeekFrom::Start(high - n))?;
+        file.write_all(head)?;
+
+        low += n;
+        high -= n;
+    }
+
+    file.seek(SeekFrom::Start(start_pos + count))?;
+    Ok(count)
+}
+
+/// Fails unless `count` bytes follow the file pointer, so that a command
+/// modifying them in place doesn't stop partway through. Returns the position.
+fn check_available(mut file: &File, count: u64) -> io::Result<u64> {
+    let start_pos = file
+        .stream_position()
+        .map_err(|_| io::Error::other(strings::NOT_SEEKABLE))?;
+    let size = match device::block_device_geometry(file)? {
+        Some(geometry) => geometry.size,
+        None => {
+            let metadata = file.metadata()?;
+            if !metadata.is_file() {
+                // Other files don't report a size, so rely on reads failing.
+                return Ok(start_pos);
+            }
+            metadata.len()
+        }
+    };
+    if start_pos.saturating_add(count) > size {
+        return Err(io::Error::other(strings::UNEXPECTED_EOF));
+    }
+
+    Ok(start_pos)
+}
+
+/// Reads `count` bytes from the file pointer a chunk at a time, passes each
+/// chunk to `modify` along with its offset from the first byte, and writes it
+/// back in place.
+fn modify_in_place(
+    mut file: &File,
+    count: u64,
+    mut modify: impl FnMut(&mut [u8], u64),
+) -> io::Result<u64> {
+    let start_pos = file
+        .stream_position()
+        .map_err(|_| io::Error::other(strings::NOT_SEEKABLE))?;
+    let mut chunk = vec![0u8; CHUNK_SIZE.min(count) as usize];
+
+    let mut offset = 0;
+    while offset < count {
+        let chunk = &mut chunk[..CHUNK_SIZE.min(count - offset) as usize];
+
+        file.read_exact(chunk).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => io::Error::other(strings::UNEXPECTED_EOF),
+            _ => e,
+        })?;
+        modify(chunk, offset);
+        file.seek(SeekFrom::Start(start_pos + offset))?;
+        file.write_all(chunk)?;
+
+        offset += chunk.len() as u64;
+    }
+
+    Ok(count)
+}
+
+/// Writes `pattern` repeatedly over `range`, a chunk at a time.
+fn fill(mut file: &File, range: &Range, pattern: &[u8]) -> io::Result<u64> {
+    let count = seek_range(file, range)?;
+
+    // Length is a multiple of the pattern's so consecutive chunks line up.
+    let chunk = pattern.repeat((CHUNK_SIZE as usize).div_ceil(pattern.len()));
+
+    let mut remaining = count;
+    while remaining > 0 {
+        let n = remaining.min(chunk.len() as u64) as usize;
+        file.write_all(&chunk[..n])?;
+        remaining -= n as u64;
+    }
+
+    Ok(count)
+}
+
+/// Writes `bytes` at `seek`, shifting the rest of the file forward.
+fn insert(mut file: &File, seek: SeekFrom, bytes: &[u8]) -> io::Result<()> {
+    let size = regular_file_size(file)?;
+    let pos = file.seek(seek)?;
+
+    if bytes.is_empty() {
+        return Ok(());
+    }
+
+    if pos < size {
+        shift_tail(file, pos, pos + bytes.len() as u64, size)?;
+    }
+
+    file.seek(SeekFrom::Start(pos))?;
+    file.write_all(bytes)
+}
+
+/// Removes the bytes in `range`, shifting the rest of the file backward.
+fn delete(mut file: &File, range: &Range) -> io::Result<()> {
+    let size = regular_file_size(file)?;
+    let count = seek_range(file, range)?;
+    let pos = file.stream_position()?;
+
+    if pos >= size {
+        return Ok(());
+    }
+
+    let end = size.min(pos.saturating_add(count));
+    if end == pos {
+        return Ok(());
+    }
+
+    shift_tail(file, end, pos, size)?;
+    file.set_len(size - (end - pos))?;
+
+    file.seek(SeekFrom::Start(pos))?;
+    Ok(())
+}
+
+fn regular_file_size(file: &File) -> io::Result<u64> {
+    let metadata = file.metadata()?;
+    if !metadata.is_file() {
+        return Err(io::Error::other(strings::NOT_REGULAR_FILE));
+    }
+    Ok(metadata.len())
+}
+
+/// Moves the bytes between `from` and `size` so that they start at `to`, a
+/// chunk at a time. Chunks are copied in the order that never overwrites
+/// bytes yet to be moved.
+fn shift_tail(mut file: &File, from: u64, to: u64, size: u64) -> io::Result<()> {
+    let mut chunk = vec![0u8; CHUNK_SIZE.min(size - from) as usize];
+
+    let mut copy = |start: u64, end: u64| -> io::Result<()> {
+        let chunk = &mut chunk[..(end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(chunk)?;
+        file.seek(SeekFrom::Start(to + (start - from)))?;
+        file.write_all(chunk)
+    };
+
+    if to > from {
+        // Moving forward. Copy from the end.
+        let mut end = size;
+        while end > from {
+            let start = from.max(end.saturating_sub(CHUNK_SIZE));
+            copy(start, end)?;
+            end = start;
+        }
+    } else {
+        // Moving backward. Copy from the start.
+        let mut start = from;
+        while start < size {
+            let end = size.min(start + CHUNK_SIZE);
+            copy(start, end)?;
+            start = end;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes each chunk at its address plus `base`. Returns the number of bytes
+/// written. Nothing is written if any chunk's address is out of range.
+fn load_chunks(mut file: &File, chunks: &[records::Chunk], base: i64) -> io::Result<usize> {
+    let positions = chunks
+        .iter()
+        .map(|chunk| {
+            chunk
+                .address
+                .checked_add_signed(base)
+                .filter(|pos| pos.checked_add(chunk.data.len() as u64).is_some())
+        })
+        .collect::<Option<Vec<u64>>>()
+        .ok_or(io::Error::other(strings::ADDRESS_OUT_OF_RANGE))?;
+
+    let mut count = 0;
+
+    for (chunk, pos) in chunks.iter().zip(positions) {
+        try_seek(file, SeekFrom::Start(pos))?;
+        file.write_all(&chunk.data)?;
+        count += chunk.data.len();
+    }
+
+    Ok(count)
+}
+
+/// Reads the bytes of a variable-length integer into `buffer` one at a time,
+/// so the file pointer ends up right after it.
+fn read_varint(mut file: &File, buffer: &mut Vec<u8>) -> io::Result<usize> {
+    buffer.clear();
+
+    let mut byte = [0u8];
+    while buffer.len() < varint::MAX_LEN {
+        if file.read(&mut byte)? == 0 {
+            return Err(io::Error::other(strings::UNEXPECTED_EOF));
+        }
+        buffer.push(byte[0]);
+
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+    }
+
+    Ok(buffer.len())
+}
+
+/// Reads into `buffer` up to and including the first NUL byte, taking at most
+/// `max` bytes, and leaves the file pointer right after the last one taken.
+fn read_c_string(mut file: &File, buffer: &mut Vec<u8>, max: Option<usize>) -> io::Result<usize> {
+    buffer.clear();
+
+    // Read ahead only if the extra bytes can be given back.
+    let seekable = try_get_pos(file).is_some();
+    let mut chunk = vec![0u8; if seekable { 4096 } else { 1 }];
+    let max = max.unwrap_or(usize::MAX);
+
+    while buffer.len() < max {
+        let want = chunk.len().min(max - buffer.len());
+        let count = file.read(&mut chunk[..want])?;
+        if count == 0 {
+            break;
+        }
+
+        if let Some(nul) = chunk[..count].iter().position(|&b| b == 0) {
+            buffer.extend_from_slice(&chunk[..=nul]);
+            if seekable {
+                file.seek(SeekFrom::Current(nul as i64 + 1 - count as i64))?;
+            }
+            break;
+        }
+
+        buffer.extend_from_slice(&chunk[..count]);
+    }
+
+    Ok(buffer.len())
+}
+
 fn read_to_buffer(
     file: &mut File,
     buffer: &mut Vec<u8>,
@@ -191,88 +1061,290 @@ fn read_to_buffer(
     Ok(actual_count)
 }
 
-fn print_hexdump(from_pos: Option<u64>, buffer: &[u8]) -> io::Result<()> {
-    const COLUMNS: usize = 16; // Must be a multiple of 2.
+fn print_hexdump(from_pos: Option<u64>, buffer: &[u8], layout: &hexdump::Layout) -> io::Result<()> {
+    let output = hexdump::format_hexdump(from_pos.unwrap_or(0), buffer, layout);
 
-    if buffer.is_empty() {
-        return Ok(());
+    io::stdout().write_all(output.as_bytes())?;
+    io::stdout().flush()?;
+
+    Ok(())
+}
+
+fn error(e: impl Into<Box<dyn Error>>) {
+    eprintln!("error: {}", e.into());
+}
+
+fn help() {
+    eprintln!("{}", *strings::HELP);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::*;
+
+    /// A file in the temp directory, removed when dropped.
+    struct TempFile {
+        path: PathBuf,
+        file: File,
     }
 
-    let from_pos = from_pos.unwrap_or(0);
+    impl TempFile {
+        fn new(name: &str, contents: &[u8]) -> TempFile {
+            let path =
+                std::env::temp_dir().join(format!("tapehead-test-{}-{name}", std::process::id()));
+            fs::write(&path, contents).unwrap();
+            let file = File::options().read(true).write(true).open(&path).unwrap();
+
+            TempFile { path, file }
+        }
 
-    let mut output = Vec::<u8>::with_capacity(4096);
+        fn contents(&self) -> Vec<u8> {
+            fs::read(&self.path).unwrap()
+        }
+    }
 
-    let (rows, last_row) = buffer.as_chunks::<COLUMNS>();
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
 
-    let last_row_offset = from_pos + (COLUMNS * (buffer.len().div_ceil(COLUMNS) - 1)) as u64;
-    let offset_width = 4.max(last_row_offset.to_string().len());
+    /// Bytes that don't repeat within a chunk, so misplaced chunks show up.
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
 
-    let print_offset = |index: usize, output: &mut Vec<u8>| {
-        let mut offset =
-            format!("{:>offset_width$}:", from_pos + (COLUMNS * index) as u64).into_bytes();
-        output.append(&mut offset);
-    };
+    fn range(start: u64, count: u64) -> Range {
+        Range {
+            seek: SeekFrom::Start(start),
+            count: Some(count),
+        }
+    }
 
-    let print_row_ascii = |row: &[u8], output: &mut Vec<u8>| {
-        output.push(b' ');
-        output.push(b' ');
+    #[test]
+    fn fill_repeats_pattern_across_chunks() {
+        let len = 2 * CHUNK_SIZE as usize + 100;
+        let temp = TempFile::new("fill", &sample(len));
 
-        for byte in row {
-            let rendered_char = if (32..=126).contains(byte) {
-                byte
-            } else {
-                &b'.'
-            };
-            output.push(*rendered_char);
+        let count = fill(&temp.file, &range(7, len as u64 - 20), b"abc").unwrap();
+
+        let mut expected = sample(len);
+        for (i, byte) in expected[7..len - 13].iter_mut().enumerate() {
+            *byte = b"abc"[i % 3];
         }
-        output.push(b'\n');
-    };
+        assert_eq!(count, len as u64 - 20);
+        assert_eq!(temp.contents(), expected);
+    }
+
+    #[test]
+    fn fill_past_end_extends_file() {
+        let temp = TempFile::new("fill-extend", b"0123");
 
-    let print_pairs = |pairs: &[[u8; 2]], output: &mut Vec<u8>| {
-        for pair in pairs {
-            let mut pair_hex = format!(" {:02x}{:02x}", pair[0], pair[1]).into_bytes();
-            output.append(&mut pair_hex);
+        fill(&temp.file, &range(2, 5), b"xy").unwrap();
+
+        assert_eq!(temp.contents(), b"01xyxyx");
+    }
+
+    #[test]
+    fn insert_shifts_tail_across_chunks() {
+        // A tail that is not a multiple of the chunk size.
+        let len = 2 * CHUNK_SIZE as usize + 123;
+        let inserts: &[(usize, Vec<u8>)] = &[
+            (5, b"hello".to_vec()),
+            (100, vec![0xee; CHUNK_SIZE as usize + 3]),
+            (0, b"x".to_vec()),
+            (len, b"end".to_vec()),
+            (5, Vec::new()),
+        ];
+
+        for (pos, bytes) in inserts {
+            let temp = TempFile::new("insert", &sample(len));
+
+            insert(&temp.file, SeekFrom::Start(*pos as u64), bytes).unwrap();
+
+            let mut expected = sample(len);
+            expected.splice(pos..pos, bytes.iter().copied());
+            assert_eq!(temp.contents(), expected, "insert at {pos}");
         }
-    };
+    }
+
+    #[test]
+    fn delete_shifts_tail_across_chunks() {
+        let len = 2 * CHUNK_SIZE as usize + 123;
+        let deletes: &[(usize, usize)] = &[
+            (5, 10),
+            (100, CHUNK_SIZE as usize + 3),
+            (0, 1),
+            (len - 50, 50),
+            // Past the end, so only the bytes up to it are removed.
+            (len - 10, 1000),
+            (5, 0),
+        ];
 
-    for (index, row) in rows.iter().enumerate() {
-        print_offset(index, &mut output);
+        for (pos, count) in deletes {
+            let temp = TempFile::new("delete", &sample(len));
 
-        let (pairs, _) = row.as_chunks::<2>();
-        print_pairs(pairs, &mut output);
+            delete(&temp.file, &range(*pos as u64, *count as u64)).unwrap();
 
-        print_row_ascii(row, &mut output);
+            let mut expected = sample(len);
+            expected.drain(*pos..len.min(pos + count));
+            assert_eq!(temp.contents(), expected, "delete {pos}:{count}");
+        }
     }
 
-    if !last_row.is_empty() {
-        print_offset(rows.len(), &mut output);
+    #[test]
+    fn apply_mask_continues_mask_across_chunks() {
+        let len = 2 * CHUNK_SIZE as usize + 123;
+        let mask: Vec<u8> = (0..CHUNK_SIZE as usize + 77)
+            .map(|i| (i % 7) as u8)
+            .collect();
+        let temp = TempFile::new("mask", &sample(len));
+        let cmd = BitsCommand {
+            seek: SeekFrom::Start(10),
+            mask: mask.clone(),
+        };
 
-        let (pairs, single) = last_row.as_chunks::<2>();
-        print_pairs(pairs, &mut output);
+        let count = apply_mask(&temp.file, &cmd, |byte, mask| byte ^ mask).unwrap();
 
-        if !single.is_empty() {
-            let mut single_hex = format!(" {:02x}", single[0]).into_bytes();
-            output.append(&mut single_hex);
-            output.push(b' '); // Fill space of missing half.
-            output.push(b' ');
+        let mut expected = sample(len);
+        for (byte, mask) in expected[10..].iter_mut().zip(&mask) {
+            *byte ^= mask;
         }
+        assert_eq!(count, mask.len() as u64);
+        assert_eq!(temp.contents(), expected);
+    }
 
-        let num_missing_pairs = (COLUMNS - last_row.len()) / 2;
-        output.extend(std::iter::repeat_n(b' ', num_missing_pairs * 5));
+    #[test]
+    fn transform_continues_key_across_chunks() {
+        let len = 2 * CHUNK_SIZE as usize + 123;
+        // A key length that doesn't divide the chunk size.
+        let key = b"abcde".to_vec();
+        let temp = TempFile::new("transform", &sample(len));
+        let cmd = TransformCommand {
+            range: range(3, len as u64 - 3),
+            op: TransformOp::Add,
+            key: key.clone(),
+        };
 
-        print_row_ascii(last_row, &mut output);
+        let count = transform(&temp.file, &cmd).unwrap();
+
+        let mut expected = sample(len);
+        for (i, byte) in expected[3..].iter_mut().enumerate() {
+            *byte = byte.wrapping_add(key[i % key.len()]);
+        }
+        assert_eq!(count, len as u64 - 3);
+        assert_eq!(temp.contents(), expected);
     }
 
-    io::stdout().write_all(&output)?;
-    io::stdout().flush()?;
+    #[test]
+    fn swap_reverses_words_across_chunks() {
+        let len = 2 * CHUNK_SIZE as usize + 123;
 
-    Ok(())
-}
+        for width in [2, 4, 8] {
+            let count = (len - 11) / width * width;
+            let temp = TempFile::new("swap", &sample(len));
 
-fn error(e: impl Into<Box<dyn Error>>) {
-    eprintln!("error: {}", e.into());
-}
+            swap(&temp.file, &range(11, count as u64), width).unwrap();
 
-fn help() {
-    eprintln!("{}", *strings::HELP);
+            let mut expected = sample(len);
+            expected[11..11 + count]
+                .chunks_exact_mut(width)
+                .for_each(<[u8]>::reverse);
+            assert_eq!(temp.contents(), expected, "width {width}");
+        }
+    }
+
+    #[test]
+    fn reverse_swaps_ends_across_chunks() {
+        let len = 3 * CHUNK_SIZE as usize + 123;
+        let counts = [
+            0,
+            1,
+            2,
+            3,
+            CHUNK_SIZE as usize + 1,
+            2 * CHUNK_SIZE as usize + 7,
+            len - 9,
+        ];
+
+        for count in counts {
+            let temp = TempFile::new("reverse", &sample(len));
+
+            let result = reverse(&temp.file, &range(9, count as u64)).unwrap();
+
+            let mut expected = sample(len);
+            expected[9..9 + count].reverse();
+            assert_eq!(result, count as u64);
+            assert_eq!(temp.contents(), expected, "count {count}");
+        }
+    }
+
+    #[test]
+    fn apply_mask_past_end_leaves_file_unchanged() {
+        let len = CHUNK_SIZE as usize + 100;
+        let temp = TempFile::new("mask-past-end", &sample(len));
+        let cmd = BitsCommand {
+            seek: SeekFrom::Start(50),
+            mask: vec![0xff; CHUNK_SIZE as usize + 51],
+        };
+
+        assert!(apply_mask(&temp.file, &cmd, |byte, mask| byte ^ mask).is_err());
+        assert_eq!(temp.contents(), sample(len));
+    }
+
+    #[test]
+    fn transform_past_end_leaves_file_unchanged() {
+        let temp = TempFile::new("transform-past-end", &vec![0; 100_000]);
+        let cmd = TransformCommand {
+            range: range(0, 200_000),
+            op: TransformOp::Xor,
+            key: vec![0xff],
+        };
+
+        assert!(transform(&temp.file, &cmd).is_err());
+        assert_eq!(temp.contents(), vec![0; 100_000]);
+    }
+
+    #[test]
+    fn swap_and_reverse_past_end_leave_file_unchanged() {
+        let temp = TempFile::new("swap-past-end", &sample(100_000));
+
+        assert!(swap(&temp.file, &range(0, 200_000), 2).is_err());
+        assert!(reverse(&temp.file, &range(0, 200_000)).is_err());
+        assert_eq!(temp.contents(), sample(100_000));
+    }
+
+    #[test]
+    fn load_chunks_checks_every_address_before_writing() {
+        let temp = TempFile::new("load", b"........");
+        let chunks = [
+            records::Chunk {
+                address: 0x102,
+                data: b"ab".to_vec(),
+            },
+            records::Chunk {
+                address: 0x105,
+                data: b"c".to_vec(),
+            },
+        ];
+
+        assert_eq!(load_chunks(&temp.file, &chunks, -0x100).unwrap(), 3);
+        assert_eq!(temp.contents(), b"..ab.c..");
+
+        let chunks = [
+            records::Chunk {
+                address: 0x10,
+                data: b"xy".to_vec(),
+            },
+            records::Chunk {
+                address: 0,
+                data: b"z".to_vec(),
+            },
+        ];
+
+        assert!(load_chunks(&temp.file, &chunks, -0x10).is_err());
+        assert_eq!(temp.contents(), b"..ab.c..");
+    }
 }
@@ -0,0 +1,170 @@
+//! Character sets for the text column of hex dumps.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Charset {
+    Ascii,
+    /// Multi-byte characters are shown at their first byte.
+    Utf8,
+    Latin1,
+    Cp437,
+    Ebcdic,
+}
+
+impl Charset {
+    pub fn parse(name: &[u8]) -> Option<Charset> {
+        match name.to_ascii_lowercase().as_slice() {
+            b"ascii" => Some(Charset::Ascii),
+            b"utf-8" | b"utf8" => Some(Charset::Utf8),
+            b"latin-1" | b"latin1" => Some(Charset::Latin1),
+            b"cp437" => Some(Charset::Cp437),
+            b"ebcdic" => Some(Charset::Ebcdic),
+            _ => None,
+        }
+    }
+
+    /// Returns the character for a byte that can be decoded on its own.
+    /// Bytes that are not characters in the charset return `None`.
+    fn decode_byte(&self, byte: u8) -> Option<char> {
+        match (self, byte) {
+            (_, 0..=0x7f) if *self != Charset::Ebcdic => Some(byte as char),
+            (Charset::Latin1, _) => Some(byte as char),
+            (Charset::Cp437, _) => Some(CP437_HIGH[byte as usize - 0x80]),
+            (Charset::Ebcdic, _) => Some(EBCDIC[byte as usize]),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Charset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Charset::Ascii => write!(f, "ascii"),
+            Charset::Utf8 => write!(f, "utf-8"),
+            Charset::Latin1 => write!(f, "latin-1"),
+            Charset::Cp437 => write!(f, "cp437"),
+            Charset::Ebcdic => write!(f, "ebcdic"),
+        }
+    }
+}
+
+/// Returns one character to show for each byte of `buffer`. Bytes that are
+/// not printable characters are shown as `.`, or as control pictures such as
+/// `␀` and `␊` if `symbols` is set. The bytes after the first of a UTF-8
+/// character are shown as spaces.
+pub fn decode_column(buffer: &[u8], charset: Charset, symbols: bool) -> Vec<char> {
+    let render = |c: char| match c {
+        c if !c.is_control() => c,
+        '\0'..='\x1f' if symbols => char::from_u32(0x2400 + c as u32).unwrap(),
+        '\x7f' if symbols => '\u{2421}',
+        _ => '.',
+    };
+
+    let mut column = Vec::with_capacity(buffer.len());
+    let mut index = 0;
+
+    while index < buffer.len() {
+        let byte = buffer[index];
+
+        if charset == Charset::Utf8 && byte >= 0x80 {
+            let len = match byte {
+                0xc2..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf4 => 4,
+                _ => 0,
+            };
+            let decoded = buffer
+                .get(index..index + len)
+                .and_then(|bytes| str::from_utf8(bytes).ok())
+                .and_then(|s| s.chars().next());
+
+            match decoded {
+                Some(c) if len > 0 => {
+                    column.push(render(c));
+                    column.extend(std::iter::repeat_n(' ', len - 1));
+                    index += len;
+                }
+                _ => {
+                    column.push('.');
+                    index += 1;
+                }
+            }
+            continue;
+        }
+
+        column.push(charset.decode_byte(byte).map_or('.', render));
+        index += 1;
+    }
+
+    column
+}
+
+/// Code page 437 characters for bytes 0x80 to 0xff.
+const CP437_HIGH: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å', 'É', 'æ', 'Æ',
+    'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ', 'á', 'í', 'ó', 'ú', 'ñ', 'Ñ',
+    'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»', '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕',
+    '╣', '║', '╗', '╝', '╜', '╛', '┐', '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦',
+    '╠', '═', '╬', '╧', '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐',
+    '▀', 'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩', '≡', '±',
+    '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{a0}',
+];
+
+/// EBCDIC (code page 037) characters for every byte.
+const EBCDIC: [char; 256] = [
+    '\u{0}', '\u{1}', '\u{2}', '\u{3}', '\u{9c}', '\u{9}', '\u{86}', '\u{7f}', '\u{97}', '\u{8d}',
+    '\u{8e}', '\u{b}', '\u{c}', '\u{d}', '\u{e}', '\u{f}', '\u{10}', '\u{11}', '\u{12}', '\u{13}',
+    '\u{9d}', '\u{85}', '\u{8}', '\u{87}', '\u{18}', '\u{19}', '\u{92}', '\u{8f}', '\u{1c}',
+    '\u{1d}', '\u{1e}', '\u{1f}', '\u{80}', '\u{81}', '\u{82}', '\u{83}', '\u{84}', '\u{a}',
+    '\u{17}', '\u{1b}', '\u{88}', '\u{89}', '\u{8a}', '\u{8b}', '\u{8c}', '\u{5}', '\u{6}',
+    '\u{7}', '\u{90}', '\u{91}', '\u{16}', '\u{93}', '\u{94}', '\u{95}', '\u{96}', '\u{4}',
+    '\u{98}', '\u{99}', '\u{9a}', '\u{9b}', '\u{14}', '\u{15}', '\u{9e}', '\u{1a}', ' ', '\u{a0}',
+    'â', 'ä', 'à', 'á', 'ã', 'å', 'ç', 'ñ', '¢', '.', '<', '(', '+', '|', '&', 'é', 'ê', 'ë', 'è',
+    'í', 'î', 'ï', 'ì', 'ß', '!', '$', '*', ')', ';', '¬', '-', '/', 'Â', 'Ä', 'À', 'Á', 'Ã', 'Å',
+    'Ç', 'Ñ', '¦', ',', '%', '_', '>', '?', 'ø', 'É', 'Ê', 'Ë', 'È', 'Í', 'Î', 'Ï', 'Ì', '`', ':',
+    '#', '@', '\'', '=', '"', 'Ø', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', '«', '»', 'ð', 'ý',
+    'þ', '±', '°', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 'ª', 'º', 'æ', '¸', 'Æ', '¤', 'µ',
+    '~', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', '¡', '¿', 'Ð', 'Ý', 'Þ', '®', '^', '£', '¥', '·',
+    '©', '§', '¶', '¼', '½', '¾', '[', ']', '¯', '¨', '´', '×', '{', 'A', 'B', 'C', 'D', 'E', 'F',
+    'G', 'H', 'I', '\u{ad}', 'ô', 'ö', 'ò', 'ó', 'õ', '}', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q',
+    'R', '¹', 'û', 'ü', 'ù', 'ú', 'ÿ', '\\', '÷', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '²', 'Ô',
+    'Ö', 'Ò', 'Ó', 'Õ', '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '³', 'Û', 'Ü', 'Ù', 'Ú',
+    '\u{9f}',
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_are_decoded_in_each_charset() {
+        let column = |bytes: &[u8], charset, symbols| {
+            decode_column(bytes, charset, symbols)
+                .into_iter()
+                .collect::<String>()
+        };
+
+        assert_eq!(column(b"Hi\n\xe9\xff", Charset::Ascii, false), "Hi...");
+        assert_eq!(
+            column(b"Hi\n\xe9\xff", Charset::Ascii, true),
+            "Hi\u{240a}.."
+        );
+        assert_eq!(
+            column(b"Hi\n\xe9\xff", Charset::Latin1, false),
+            "Hi.\u{e9}\u{ff}"
+        );
+        assert_eq!(
+            column(b"\x81\xdb\x7f", Charset::Cp437, true),
+            "\u{fc}\u{2588}\u{2421}"
+        );
+        assert_eq!(
+            column(b"\xc8\x89\x25\x40", Charset::Ebcdic, true),
+            "Hi\u{240a} "
+        );
+        assert_eq!(
+            column(b"a\xc3\xa9\xe2\x82\xacb\xc3", Charset::Utf8, false),
+            "a\u{e9} \u{20ac}  b."
+        );
+    }
+}
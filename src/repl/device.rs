@@ -0,0 +1,235 @@
+//! Block device geometry, which `File::metadata` does not report.
+
+use std::{fs::File, io};
+
+#[derive(Debug, PartialEq)]
+pub struct Geometry {
+    pub size: u64,
+    pub logical_sector_size: u32,
+    pub physical_sector_size: u32,
+}
+
+/// Something that can be asked for block device geometry. Implemented for
+/// `File` with the platform's queries, and by fakes in tests.
+pub trait GeometrySource {
+    /// Returns whether this is a block device whose geometry can be queried.
+    fn is_block_device(&self) -> io::Result<bool>;
+    fn size(&self) -> io::Result<u64>;
+    fn logical_sector_size(&self) -> io::Result<u32>;
+    fn physical_sector_size(&self) -> io::Result<u32>;
+}
+
+/// Returns the geometry of `source` if it is a block device, or `None` if it
+/// is not, or if the platform gives no way to query it.
+pub fn block_device_geometry(source: &impl GeometrySource) -> io::Result<Option<Geometry>> {
+    if !source.is_block_device()? {
+        return Ok(None);
+    }
+
+    Ok(Some(Geometry {
+        size: source.size()?,
+        logical_sector_size: source.logical_sector_size()?,
+        physical_sector_size: source.physical_sector_size()?,
+    }))
+}
+
+impl GeometrySource for File {
+    #[cfg(target_os = "linux")]
+    fn is_block_device(&self) -> io::Result<bool> {
+        use std::os::unix::fs::FileTypeExt;
+
+        Ok(self.metadata()?.file_type().is_block_device())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn is_block_device(&self) -> io::Result<bool> {
+        Ok(false)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        sys::size(self)
+    }
+
+    fn logical_sector_size(&self) -> io::Result<u32> {
+        sys::logical_sector_size(self)
+    }
+
+    fn physical_sector_size(&self) -> io::Result<u32> {
+        sys::physical_sector_size(self)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use std::{
+        ffi::{c_int, c_uint, c_ulong},
+        fs::File,
+        io,
+        os::fd::AsRawFd,
+    };
+
+    unsafe extern "C" {
+        fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+    }
+
+    // The direction bits of an ioctl request number sit higher on these
+    // architectures, leaving 13 bits for the size instead of 14.
+    #[cfg(any(
+        target_arch = "powerpc",
+        target_arch = "powerpc64",
+        target_arch = "mips",
+        target_arch = "mips64",
+        target_arch = "mips32r6",
+        target_arch = "mips64r6",
+        target_arch = "sparc",
+        target_arch = "sparc64",
+    ))]
+    const IOC_DIRSHIFT: c_ulong = 29;
+    #[cfg(not(any(
+        target_arch = "powerpc",
+        target_arch = "powerpc64",
+        target_arch = "mips",
+        target_arch = "mips64",
+        target_arch = "mips32r6",
+        target_arch = "mips64r6",
+        target_arch = "sparc",
+        target_arch = "sparc64",
+    )))]
+    const IOC_DIRSHIFT: c_ulong = 30;
+    const IOC_READ: c_ulong = 2;
+
+    /// The `_IOR` macro from <asm/ioctl.h>.
+    pub(super) const fn ior(kind: u8, nr: u8, size: usize) -> c_ulong {
+        IOC_READ << IOC_DIRSHIFT | (size as c_ulong) << 16 | (kind as c_ulong) << 8 | nr as c_ulong
+    }
+
+    // Request numbers from <linux/fs.h>.
+    const BLKSSZGET: c_ulong = 0x1268;
+    const BLKPBSZGET: c_ulong = 0x127b;
+    pub(super) const BLKGETSIZE64: c_ulong = ior(0x12, 114, size_of::<usize>());
+
+    pub fn size(file: &File) -> io::Result<u64> {
+        ioctl_read(file, BLKGETSIZE64)
+    }
+
+    pub fn logical_sector_size(file: &File) -> io::Result<u32> {
+        ioctl_read::<c_int>(file, BLKSSZGET).map(|size| size as u32)
+    }
+
+    pub fn physical_sector_size(file: &File) -> io::Result<u32> {
+        ioctl_read::<c_uint>(file, BLKPBSZGET)
+    }
+
+    fn ioctl_read<T: Default>(file: &File, request: c_ulong) -> io::Result<T> {
+        let mut value = T::default();
+
+        // SAFETY: The descriptor is open for the lifetime of `file`, and each
+        // request writes exactly one `T` through the pointer.
+        let ret = unsafe { ioctl(file.as_raw_fd(), request, &mut value as *mut T) };
+        if ret == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(value)
+    }
+}
+
+/// No queries, as `File::is_block_device` is always false here.
+#[cfg(not(target_os = "linux"))]
+mod sys {
+    use std::{fs::File, io};
+
+    pub fn size(_file: &File) -> io::Result<u64> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    pub fn logical_sector_size(_file: &File) -> io::Result<u32> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    pub fn physical_sector_size(_file: &File) -> io::Result<u32> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 1 GiB disk with 512-byte logical sectors.
+    struct FakeDevice {
+        is_block_device: bool,
+        /// `None` makes the query fail.
+        physical_sector_size: Option<u32>,
+    }
+
+    impl GeometrySource for FakeDevice {
+        fn is_block_device(&self) -> io::Result<bool> {
+            Ok(self.is_block_device)
+        }
+
+        fn size(&self) -> io::Result<u64> {
+            Ok(1 << 30)
+        }
+
+        fn logical_sector_size(&self) -> io::Result<u32> {
+            Ok(512)
+        }
+
+        fn physical_sector_size(&self) -> io::Result<u32> {
+            self.physical_sector_size
+                .ok_or(io::ErrorKind::Unsupported.into())
+        }
+    }
+
+    #[test]
+    fn regular_file_has_no_geometry() {
+        let file = File::open(file!()).unwrap();
+
+        assert_eq!(block_device_geometry(&file).unwrap(), None);
+    }
+
+    #[test]
+    fn device_geometry_comes_from_source() {
+        let device = FakeDevice {
+            is_block_device: true,
+            physical_sector_size: Some(4096),
+        };
+
+        assert_eq!(
+            block_device_geometry(&device).unwrap(),
+            Some(Geometry {
+                size: 1 << 30,
+                logical_sector_size: 512,
+                physical_sector_size: 4096,
+            })
+        );
+    }
+
+    #[test]
+    fn non_device_is_not_queried() {
+        let device = FakeDevice {
+            is_block_device: false,
+            physical_sector_size: None,
+        };
+
+        assert_eq!(block_device_geometry(&device).unwrap(), None);
+    }
+
+    #[test]
+    fn failed_device_query_returns_err() {
+        let device = FakeDevice {
+            is_block_device: true,
+            physical_sector_size: None,
+        };
+
+        assert!(block_device_geometry(&device).is_err());
+    }
+
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    #[test]
+    fn blkgetsize64_matches_linux_header() {
+        assert_eq!(sys::BLKGETSIZE64, 0x8008_1272);
+        assert_eq!(sys::ior(0x12, 114, 4), 0x8004_1272);
+    }
+}
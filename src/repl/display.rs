@@ -0,0 +1,139 @@
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BitOrder {
+    MsbFirst,
+    LsbFirst,
+}
+
+/// Formats `bytes` read from `from_pos` in binary, a few bytes per row, under
+/// a header of bit indices.
+pub fn format_bits(from_pos: u64, bytes: &[u8], order: BitOrder) -> String {
+    const COLUMNS: usize = 4;
+
+    let last_row_offset = from_pos + (COLUMNS * (bytes.len().div_ceil(COLUMNS).max(1) - 1)) as u64;
+    let offset_width = 4.max(last_row_offset.to_string().len());
+
+    let indices = match order {
+        BitOrder::MsbFirst => "76543210",
+        BitOrder::LsbFirst => "01234567",
+    };
+
+    let mut output = format!("{:>offset_width$} ", "");
+    for _ in 0..COLUMNS.min(bytes.len()) {
+        write!(output, " {indices}").unwrap();
+    }
+    output.push('\n');
+
+    for (index, row) in bytes.chunks(COLUMNS).enumerate() {
+        write!(
+            output,
+            "{:>offset_width$}:",
+            from_pos + (COLUMNS * index) as u64
+        )
+        .unwrap();
+        for byte in row {
+            let byte = match order {
+                BitOrder::MsbFirst => *byte,
+                BitOrder::LsbFirst => byte.reverse_bits(),
+            };
+            write!(output, " {byte:08b}").unwrap();
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Quotes `bytes` as a string, escaping anything that isn't printable ASCII.
+pub fn quote(bytes: &[u8]) -> String {
+    format!("\"{}\"", bytes.escape_ascii())
+}
+
+/// Escapes bytes that could disturb a terminal, like `escape_ascii` but
+/// leaving newlines and tabs as they are so text stays readable.
+pub fn escape_control(bytes: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(bytes.len());
+    for byte in bytes {
+        match byte {
+            b'\n' | b'\t' => output.push(*byte),
+            b'\\' => output.extend_from_slice(b"\\\\"),
+            32..=126 => output.push(*byte),
+            _ => output.extend(byte.escape_ascii()),
+        }
+    }
+    output
+}
+
+/// Formats seconds since the Unix epoch as a UTC date and time, e.g.
+/// `2024-02-29 13:45:00 UTC`.
+pub fn format_unix_time(secs: i64) -> String {
+    let days = secs.div_euclid(86400);
+    let secs_of_day = secs.rem_euclid(86400);
+
+    // Days to civil date. See http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unix_time_is_formatted_correctly() {
+        let inputs: &[(i64, &str)] = &[
+            (0, "1970-01-01 00:00:00 UTC"),
+            (-1, "1969-12-31 23:59:59 UTC"),
+            (951782400, "2000-02-29 00:00:00 UTC"),
+            (1709214300, "2024-02-29 13:45:00 UTC"),
+        ];
+
+        for (secs, formatted) in inputs {
+            assert_eq!(format_unix_time(*secs), *formatted);
+        }
+    }
+
+    #[test]
+    fn control_bytes_are_escaped() {
+        let escaped = escape_control(b"a\tb\n\x1b[2J\\\r\xff");
+
+        assert_eq!(escaped, b"a\tb\n\\x1b[2J\\\\\\r\\xff");
+    }
+
+    #[test]
+    fn bits_are_formatted_correctly() {
+        let bytes = [0x01, 0x80, 0x0f, 0xf0, 0xaa];
+
+        assert_eq!(
+            format_bits(6, &bytes, BitOrder::MsbFirst)
+                .lines()
+                .collect::<Vec<_>>(),
+            [
+                "      76543210 76543210 76543210 76543210",
+                "   6: 00000001 10000000 00001111 11110000",
+                "  10: 10101010",
+            ]
+        );
+        assert_eq!(
+            format_bits(0, &bytes[..2], BitOrder::LsbFirst)
+                .lines()
+                .collect::<Vec<_>>(),
+            ["      01234567 01234567", "   0: 10000000 00000001"]
+        );
+    }
+}
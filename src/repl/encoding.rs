@@ -0,0 +1,317 @@
+//! Text encodings of binary data.
+
+use std::fmt::Write;
+
+use crate::strings;
+
+/// An encoding that can be decoded by `writeenc`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    Base64,
+    Base32,
+    /// Hex digits, e.g. `deadbeef`. Whitespace is ignored.
+    Hex,
+    /// `0x`-prefixed bytes separated by commas, e.g. `0xde, 0xad`.
+    Array,
+    /// Output of `xxd`.
+    Xxd,
+    /// Output of `hexdump -C`.
+    Hexdump,
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+impl Encoding {
+    pub fn parse(name: &[u8]) -> Option<Encoding> {
+        match name.to_ascii_lowercase().as_slice() {
+            b"base64" => Some(Encoding::Base64),
+            b"base32" => Some(Encoding::Base32),
+            b"hex" => Some(Encoding::Hex),
+            b"array" => Some(Encoding::Array),
+            b"xxd" => Some(Encoding::Xxd),
+            b"hexdump" => Some(Encoding::Hexdump),
+            _ => None,
+        }
+    }
+
+    pub fn decode(&self, text: &[u8]) -> Result<Vec<u8>, &'static str> {
+        let decoded = match self {
+            Encoding::Base64 => decode_base2n(text, BASE64_ALPHABET, 6),
+            Encoding::Base32 => decode_base2n(text, BASE32_ALPHABET, 5),
+            Encoding::Hex => decode_hex(text),
+            Encoding::Array => decode_array(text),
+            Encoding::Xxd | Encoding::Hexdump => decode_dump(text, *self),
+        };
+
+        decoded.ok_or(strings::INVALID_ENCODED_DATA)
+    }
+}
+
+/// Encodes `bytes` as contiguous lowercase hex digits.
+pub fn encode_hex(bytes: &[u8]) -> String {
+    let mut output = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        write!(output, "{byte:02x}").unwrap();
+    }
+    output
+}
+
+/// Encodes `bytes` in standard base64, with `=` padding.
+pub fn encode_base64(bytes: &[u8]) -> String {
+    encode_base2n(bytes, BASE64_ALPHABET, 6, 4)
+}
+
+/// Encodes `bytes` in RFC 4648 base32, with `=` padding.
+pub fn encode_base32(bytes: &[u8]) -> String {
+    encode_base2n(bytes, BASE32_ALPHABET, 5, 8)
+}
+
+/// Encodes `bytes` with an alphabet of `2^bits` digits, padding the output
+/// with `=` to a whole number of blocks of `block_digits`.
+fn encode_base2n(bytes: &[u8], alphabet: &[u8], bits: u32, block_digits: usize) -> String {
+    let mut output = String::with_capacity(bytes.len() * 2);
+    let mut acc: u32 = 0;
+    let mut acc_bits = 0;
+
+    for byte in bytes {
+        acc = acc << 8 | *byte as u32;
+        acc_bits += 8;
+        while acc_bits >= bits {
+            acc_bits -= bits;
+            output.push(alphabet[(acc >> acc_bits) as usize & (alphabet.len() - 1)] as char);
+        }
+    }
+    if acc_bits > 0 {
+        output.push(alphabet[(acc << (bits - acc_bits)) as usize & (alphabet.len() - 1)] as char);
+    }
+    while !output.len().is_multiple_of(block_digits) {
+        output.push('=');
+    }
+
+    output
+}
+
+/// Decodes digits of an alphabet of `2^bits` digits. Whitespace is ignored,
+/// and so is `=` padding at the end.
+fn decode_base2n(text: &[u8], alphabet: &[u8], bits: u32) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(text.len());
+    let mut acc: u32 = 0;
+    let mut acc_bits = 0;
+    let mut padding = false;
+
+    for c in text.iter().filter(|c| !c.is_ascii_whitespace()) {
+        if *c == b'=' {
+            padding = true;
+            continue;
+        }
+        if padding {
+            return None;
+        }
+        let digit = alphabet.iter().position(|d| d == c)?;
+        acc = acc << bits | digit as u32;
+        acc_bits += bits;
+        if acc_bits >= 8 {
+            acc_bits -= 8;
+            output.push((acc >> acc_bits) as u8);
+        }
+    }
+
+    Some(output)
+}
+
+pub fn decode_hex(text: &[u8]) -> Option<Vec<u8>> {
+    let digits: Vec<u8> = text
+        .iter()
+        .filter(|c| !c.is_ascii_whitespace())
+        .copied()
+        .collect();
+    let (pairs, []) = digits.as_chunks::<2>() else {
+        return None;
+    };
+
+    pairs.iter().map(|pair| parse_hex_byte(pair)).collect()
+}
+
+fn decode_array(text: &[u8]) -> Option<Vec<u8>> {
+    text.split(|c| *c == b',' || c.is_ascii_whitespace())
+        .filter(|item| !item.is_empty())
+        .map(|item| match item {
+            [b'0', b'x' | b'X', digits @ ..] if (1..=2).contains(&digits.len()) => {
+                parse_hex_byte(digits)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Reverses a hex dump from `xxd` or `hexdump -C`. Each row is placed at its
+/// offset from the first row, and a `*` line repeats the row before it up to
+/// the next offset.
+fn decode_dump(text: &[u8], encoding: Encoding) -> Option<Vec<u8>> {
+    let mut output = Vec::new();
+    let mut first_offset = None;
+    let mut prev_row = 0..0;
+    let mut repeating = false;
+
+    for line in text.split(|c| *c == b'\n') {
+        let line = line.trim_ascii();
+        if line.is_empty() {
+            continue;
+        }
+        if line == b"*" {
+            repeating = true;
+            continue;
+        }
+
+        let (offset, row) = match encoding {
+            Encoding::Xxd => parse_xxd_line(line)?,
+            _ => parse_hexdump_line(line)?,
+        };
+        let pos = offset.checked_sub(*first_offset.get_or_insert(offset))?;
+        let pos = usize::try_from(pos).ok()?;
+
+        if repeating {
+            if prev_row.is_empty() {
+                return None;
+            }
+            while output.len() < pos {
+                let len = prev_row.len().min(pos - output.len());
+                output.extend_from_within(prev_row.start..prev_row.start + len);
+            }
+            repeating = false;
+        }
+        if pos != output.len() {
+            return None;
+        }
+
+        prev_row = output.len()..output.len() + row.len();
+        output.extend_from_slice(&row);
+    }
+
+    Some(output)
+}
+
+/// Parses a line like `00000010: 4865 6c6c 6f0a  Hello.` into its offset
+/// and bytes. The text column starts after two spaces.
+fn parse_xxd_line(line: &[u8]) -> Option<(u64, Vec<u8>)> {
+    let colon = line.iter().position(|c| *c == b':')?;
+    let offset = parse_hex_offset(&line[..colon])?;
+
+    let cells = &line[colon + 1..];
+    let cells = cells.strip_prefix(b" ").unwrap_or(cells);
+    let cells_end = cells
+        .windows(2)
+        .position(|pair| pair == b"  ")
+        .unwrap_or(cells.len());
+
+    Some((offset, decode_hex(&cells[..cells_end])?))
+}
+
+/// Parses a line like `00000010  48 65 6c 6c 6f 0a  |Hello.|` into its
+/// offset and bytes. The last line of a dump holds only the end offset.
+fn parse_hexdump_line(line: &[u8]) -> Option<(u64, Vec<u8>)> {
+    let cells_end = line.iter().position(|c| *c == b'|').unwrap_or(line.len());
+    let mut words = line[..cells_end]
+        .split(u8::is_ascii_whitespace)
+        .filter(|word| !word.is_empty());
+
+    let offset = parse_hex_offset(words.next()?)?;
+    let bytes = words
+        .map(|word| match word {
+            [_, _] => parse_hex_byte(word),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+
+    Some((offset, bytes))
+}
+
+fn parse_hex_offset(digits: &[u8]) -> Option<u64> {
+    u64::from_str_radix(str::from_utf8(digits).ok()?, 16).ok()
+}
+
+fn parse_hex_byte(digits: &[u8]) -> Option<u8> {
+    if !digits.iter().all(u8::is_ascii_hexdigit) {
+        return None;
+    }
+    u8::from_str_radix(str::from_utf8(digits).ok()?, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_are_encoded_correctly() {
+        assert_eq!(encode_hex(b"\x00\xab\x10"), "00ab10");
+
+        let base64: &[(&[u8], &str)] = &[
+            (b"", ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foobar", "Zm9vYmFy"),
+        ];
+        for (bytes, encoded) in base64 {
+            assert_eq!(encode_base64(bytes), *encoded);
+        }
+
+        let base32: &[(&[u8], &str)] = &[
+            (b"", ""),
+            (b"f", "MY======"),
+            (b"fo", "MZXQ===="),
+            (b"foob", "MZXW6YQ="),
+            (b"foobar", "MZXW6YTBOI======"),
+        ];
+        for (bytes, encoded) in base32 {
+            assert_eq!(encode_base32(bytes), *encoded);
+        }
+    }
+
+    #[test]
+    fn text_is_decoded_correctly() {
+        let inputs: &[(Encoding, &[u8], &[u8])] = &[
+            (Encoding::Base64, b"Zm9v\nYmE=", b"fooba"),
+            (Encoding::Base32, b"MZXW6YQ=", b"foob"),
+            (Encoding::Hex, b"DEad be ef", b"\xde\xad\xbe\xef"),
+            (Encoding::Array, b"0xde, 0xAD,0x1,", b"\xde\xad\x01"),
+            (
+                Encoding::Xxd,
+                b"00000010: 4865 6c6c  Hell\n00000014: 6f    o",
+                b"Hello",
+            ),
+            (
+                Encoding::Hexdump,
+                b"00000000  61 62 63 64 61 62 63 64  |abcdabcd|\n\
+                  *\n\
+                  00000014  0a  |.|\n\
+                  00000015\n",
+                b"abcdabcdabcdabcdabcd\n",
+            ),
+        ];
+
+        for (encoding, text, bytes) in inputs {
+            assert_eq!(encoding.decode(text).as_deref(), Ok(*bytes));
+        }
+    }
+
+    #[test]
+    fn invalid_text_returns_err() {
+        let inputs: &[(Encoding, &[u8])] = &[
+            (Encoding::Base64, b"Zm9v!"),
+            (Encoding::Base64, b"Zg==Zg=="),
+            (Encoding::Hex, b"abc"),
+            (Encoding::Hex, b"+1"),
+            (Encoding::Array, b"0x123"),
+            (Encoding::Array, b"12"),
+            (Encoding::Xxd, b"00000000 4142"),
+            (Encoding::Hexdump, b"00000000  41\n00000004  42"),
+        ];
+
+        for (encoding, text) in inputs {
+            assert!(encoding.decode(text).is_err());
+        }
+    }
+}
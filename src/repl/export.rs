@@ -0,0 +1,124 @@
+//! Formats for exporting bytes with the `dump` command, as source code or
+//! text encodings.
+
+use std::fmt::Write;
+
+use crate::repl::{encoding, records};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    /// A C array, like `xxd -i`.
+    C,
+    /// A Rust `[u8; N]` constant.
+    Rust,
+    /// A Python bytes literal.
+    Python,
+    Base64,
+    Base32,
+    /// Contiguous hex digits.
+    Hex,
+    Ihex,
+    Srec,
+}
+
+/// Array elements per line in source code.
+const ELEMENTS_PER_LINE: usize = 12;
+/// Line length of base64 and base32 output, as used by the `base64` tool.
+const ENCODED_LINE_LEN: usize = 76;
+
+impl Format {
+    pub fn parse(name: &[u8]) -> Option<Format> {
+        match name.to_ascii_lowercase().as_slice() {
+            b"c" => Some(Format::C),
+            b"rust" => Some(Format::Rust),
+            b"python" => Some(Format::Python),
+            b"base64" => Some(Format::Base64),
+            b"base32" => Some(Format::Base32),
+            b"hex" => Some(Format::Hex),
+            b"ihex" => Some(Format::Ihex),
+            b"srec" => Some(Format::Srec),
+            _ => None,
+        }
+    }
+
+    /// Formats `bytes` read from `from_pos`. The position is only used as
+    /// the address of Intel HEX and S-records.
+    pub fn format(&self, from_pos: u64, bytes: &[u8]) -> Result<String, &'static str> {
+        let output = match self {
+            Format::C => format!(
+                "unsigned char data[] = {{\n{}}};\nunsigned int data_len = {};\n",
+                format_elements(bytes, "  "),
+                bytes.len()
+            ),
+            Format::Rust => format!(
+                "const DATA: [u8; {}] = [\n{}];\n",
+                bytes.len(),
+                format_elements(bytes, "    ")
+            ),
+            Format::Python => format!("data = b\"{}\"\n", bytes.escape_ascii()),
+            Format::Base64 => wrap(&encoding::encode_base64(bytes)),
+            Format::Base32 => wrap(&encoding::encode_base32(bytes)),
+            Format::Hex => format!("{}\n", encoding::encode_hex(bytes)),
+            Format::Ihex => records::format_ihex(from_pos, bytes)?,
+            Format::Srec => records::format_srec(from_pos, bytes)?,
+        };
+
+        Ok(output)
+    }
+}
+
+/// Formats `bytes` as comma-separated hex literals, a line at a time.
+fn format_elements(bytes: &[u8], indent: &str) -> String {
+    let mut output = String::new();
+    for line in bytes.chunks(ELEMENTS_PER_LINE) {
+        output.push_str(indent);
+        for (i, byte) in line.iter().enumerate() {
+            if i > 0 {
+                output.push(' ');
+            }
+            write!(output, "0x{byte:02x},").unwrap();
+        }
+        output.push('\n');
+    }
+    output
+}
+
+fn wrap(encoded: &str) -> String {
+    let mut output = String::with_capacity(encoded.len() + encoded.len() / ENCODED_LINE_LEN + 1);
+    for line in encoded.as_bytes().chunks(ENCODED_LINE_LEN) {
+        output.push_str(str::from_utf8(line).unwrap());
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_code_formats_are_correct() {
+        let bytes = b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\"\xff";
+        let output = |format: Format| format.format(0, bytes).unwrap();
+
+        assert_eq!(
+            output(Format::C),
+            "unsigned char data[] = {\n  \
+             0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b,\n  \
+             0x22, 0xff,\n\
+             };\n\
+             unsigned int data_len = 14;\n"
+        );
+        assert_eq!(
+            output(Format::Rust),
+            "const DATA: [u8; 14] = [\n    \
+             0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b,\n    \
+             0x22, 0xff,\n\
+             ];\n"
+        );
+        assert_eq!(
+            output(Format::Python),
+            "data = b\"\\x00\\x01\\x02\\x03\\x04\\x05\\x06\\x07\\x08\\t\\n\\x0b\\\"\\xff\"\n"
+        );
+    }
+}
@@ -0,0 +1,556 @@
+//! Hex dumps of bytes read from a file. The bytes can also be shown in other
+//! radices.
+
+use std::fmt::{self, Write};
+
+use crate::{
+    repl::{
+        charset::{Charset, decode_column},
+        settings::{format_switch, parse_switch},
+        value::{Endian, parse_integer},
+    },
+    strings,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OffsetRadix {
+    Decimal,
+    Hex,
+}
+
+/// The radix bytes are shown in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Radix {
+    Hex,
+    Octal,
+    Decimal,
+    Binary,
+}
+
+impl Radix {
+    /// Digits for a value `len` bytes wide.
+    fn cell_width(&self, len: usize) -> usize {
+        match self {
+            Radix::Hex => len * 2,
+            Radix::Octal => (len * 8).div_ceil(3),
+            Radix::Decimal => (u64::MAX >> (64 - len * 8)).to_string().len(),
+            Radix::Binary => len * 8,
+        }
+    }
+
+    fn write_cell(&self, output: &mut String, value: u64, len: usize) {
+        let width = self.cell_width(len);
+        match self {
+            Radix::Hex => write!(output, "{value:0width$x}"),
+            Radix::Octal => write!(output, "{value:0width$o}"),
+            Radix::Decimal => write!(output, "{value:>width$}"),
+            Radix::Binary => write!(output, "{value:0width$b}"),
+        }
+        .unwrap();
+    }
+}
+
+/// How a hex dump is laid out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    /// Bytes per row.
+    pub width: usize,
+    /// Bytes per space-separated group.
+    pub group: usize,
+    pub offset_radix: OffsetRadix,
+    pub radix: Radix,
+    /// Collapse runs of identical rows into a `*` line.
+    pub squeeze: bool,
+    /// Show each group as one word in this byte order, rather than as
+    /// separate bytes.
+    pub words: Option<Endian>,
+    /// Character set of the text column.
+    pub charset: Charset,
+    /// Show control characters in the text column as symbols.
+    pub symbols: bool,
+    /// Colour bytes by class with terminal escape codes.
+    pub color: bool,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout {
+            width: 16,
+            group: 2,
+            offset_radix: OffsetRadix::Decimal,
+            radix: Radix::Hex,
+            squeeze: true,
+            words: None,
+            charset: Charset::Ascii,
+            symbols: false,
+            color: false,
+        }
+    }
+}
+
+/// A change to one property of a `Layout`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LayoutOption {
+    Width(usize),
+    Group(usize),
+    Offset(OffsetRadix),
+    Radix(Radix),
+    Squeeze(bool),
+    Words(Option<Endian>),
+    Charset(Charset),
+    Symbols(bool),
+    Color(bool),
+}
+
+const MAX_WIDTH: u64 = 256;
+
+impl LayoutOption {
+    /// Parses an option from its name, e.g. `width`, and value.
+    pub fn parse(name: &[u8], value: &[u8]) -> Result<LayoutOption, &'static str> {
+        let value = value.to_ascii_lowercase();
+        let value = value.as_slice();
+
+        match name.to_ascii_lowercase().as_slice() {
+            b"width" => match parse_integer(value) {
+                Some(width @ 1..=MAX_WIDTH) => Ok(LayoutOption::Width(width as usize)),
+                _ => Err(strings::INVALID_HEX_WIDTH),
+            },
+            b"group" => match value {
+                b"1" => Ok(LayoutOption::Group(1)),
+                b"2" => Ok(LayoutOption::Group(2)),
+                b"4" => Ok(LayoutOption::Group(4)),
+                b"8" => Ok(LayoutOption::Group(8)),
+                _ => Err(strings::INVALID_HEX_GROUP),
+            },
+            b"offset" => match value {
+                b"dec" => Ok(LayoutOption::Offset(OffsetRadix::Decimal)),
+                b"hex" => Ok(LayoutOption::Offset(OffsetRadix::Hex)),
+                _ => Err(strings::INVALID_HEX_OFFSET),
+            },
+            b"radix" => match value {
+                b"hex" => Ok(LayoutOption::Radix(Radix::Hex)),
+                b"oct" => Ok(LayoutOption::Radix(Radix::Octal)),
+                b"dec" => Ok(LayoutOption::Radix(Radix::Decimal)),
+                b"bin" => Ok(LayoutOption::Radix(Radix::Binary)),
+                _ => Err(strings::INVALID_HEX_RADIX),
+            },
+            b"squeeze" => parse_switch(value).map(LayoutOption::Squeeze),
+            b"words" => match value {
+                b"off" => Ok(LayoutOption::Words(None)),
+                b"le" => Ok(LayoutOption::Words(Some(Endian::Little))),
+                b"be" => Ok(LayoutOption::Words(Some(Endian::Big))),
+                _ => Err(strings::INVALID_HEX_WORDS),
+            },
+            b"charset" => Charset::parse(value)
+                .map(LayoutOption::Charset)
+                .ok_or(strings::INVALID_HEX_CHARSET),
+            b"symbols" => parse_switch(value).map(LayoutOption::Symbols),
+            b"color" => parse_switch(value).map(LayoutOption::Color),
+            _ => Err(strings::UNKNOWN_SETTING),
+        }
+    }
+}
+
+impl Layout {
+    pub fn apply(&mut self, option: LayoutOption) {
+        match option {
+            LayoutOption::Width(width) => self.width = width,
+            LayoutOption::Group(group) => self.group = group,
+            LayoutOption::Offset(radix) => self.offset_radix = radix,
+            LayoutOption::Radix(radix) => self.radix = radix,
+            LayoutOption::Squeeze(squeeze) => self.squeeze = squeeze,
+            LayoutOption::Words(words) => self.words = words,
+            LayoutOption::Charset(charset) => self.charset = charset,
+            LayoutOption::Symbols(symbols) => self.symbols = symbols,
+            LayoutOption::Color(color) => self.color = color,
+        }
+    }
+
+    /// Returns the name and value of each property, for listing.
+    pub fn entries(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("width", self.width.to_string()),
+            ("group", self.group.to_string()),
+            ("offset", self.offset_radix.to_string()),
+            ("radix", self.radix.to_string()),
+            ("squeeze", format_switch(self.squeeze)),
+            (
+                "words",
+                match self.words {
+                    None => "off",
+                    Some(Endian::Little) => "le",
+                    Some(Endian::Big) => "be",
+                }
+                .to_string(),
+            ),
+            ("charset", self.charset.to_string()),
+            ("symbols", format_switch(self.symbols)),
+            ("color", format_switch(self.color)),
+        ]
+    }
+}
+
+impl fmt::Display for OffsetRadix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OffsetRadix::Decimal => write!(f, "dec"),
+            OffsetRadix::Hex => write!(f, "hex"),
+        }
+    }
+}
+
+impl fmt::Display for Radix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Radix::Hex => write!(f, "hex"),
+            Radix::Octal => write!(f, "oct"),
+            Radix::Decimal => write!(f, "dec"),
+            Radix::Binary => write!(f, "bin"),
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+
+/// Returns the escape code for the colour of a byte's class.
+fn byte_color(byte: u8) -> &'static str {
+    match byte {
+        0x00 => "\x1b[90m",
+        b' ' | b'\t' | b'\n' | 0x0b | 0x0c | b'\r' => "\x1b[32m",
+        0x21..=0x7e => "\x1b[36m",
+        0x01..=0x1f | 0x7f => "\x1b[35m",
+        0xff => "\x1b[31m",
+        _ => "\x1b[33m",
+    }
+}
+
+/// Formats `buffer` read from `from_pos` as rows of offset, bytes and ASCII.
+pub fn format_hexdump(from_pos: u64, buffer: &[u8], layout: &Layout) -> String {
+    let Layout {
+        width,
+        group,
+        offset_radix,
+        radix,
+        squeeze,
+        words,
+        charset,
+        symbols,
+        color,
+    } = *layout;
+
+    let mut output = String::with_capacity(4096);
+
+    if buffer.is_empty() {
+        return output;
+    }
+
+    let last_row_offset = from_pos + (width * (buffer.len().div_ceil(width) - 1)) as u64;
+    let offset_width = match offset_radix {
+        OffsetRadix::Decimal => 4.max(last_row_offset.to_string().len()),
+        OffsetRadix::Hex => 8.max(format!("{last_row_offset:x}").len()),
+    };
+
+    // Hex digits of a group run together. Other radices have a space between
+    // bytes, and an extra one between groups. Words are single numbers, so
+    // need no extra space.
+    let (group_sep, byte_sep) = match (radix, words) {
+        (Radix::Hex, _) | (_, Some(_)) => ("", ""),
+        _ => (" ", " "),
+    };
+    let group_width = |len: usize| match words {
+        Some(_) => 1 + radix.cell_width(len),
+        None => 1 + group_sep.len() + len * radix.cell_width(1) + (len - 1) * byte_sep.len(),
+    };
+    // The last group of a row can be short if the width isn't a multiple of
+    // the group size.
+    let rem = width % group;
+    let cells_width =
+        (width / group) * group_width(group) + if rem > 0 { group_width(rem) } else { 0 };
+
+    let column = decode_column(buffer, charset, symbols);
+    let num_rows = buffer.len().div_ceil(width);
+    let mut prev_row: Option<&[u8]> = None;
+    let mut squeezing = false;
+
+    for (index, row) in buffer.chunks(width).enumerate() {
+        // The last row is always shown, so the end offset can be seen.
+        if squeeze && prev_row == Some(row) && index + 1 < num_rows {
+            if !squeezing {
+                output.push_str("*\n");
+                squeezing = true;
+            }
+            continue;
+        }
+        prev_row = Some(row);
+        squeezing = false;
+
+        let offset = from_pos + (width * index) as u64;
+        match offset_radix {
+            OffsetRadix::Decimal => write!(output, "{offset:>offset_width$}:"),
+            OffsetRadix::Hex => write!(output, "{offset:0offset_width$x}:"),
+        }
+        .unwrap();
+
+        let mut cells_len = 0;
+        for (group_index, bytes) in row.chunks(group).enumerate() {
+            output.push(' ');
+            output.push_str(group_sep);
+            // Words span several bytes, so only the bytes of other dumps are
+            // coloured.
+            match words {
+                Some(endian) => {
+                    // A word cut short by the end of the buffer is padded
+                    // with zero bytes, like `od` does.
+                    let len = group.min(width - group_index * group);
+                    let mut word = [0; 8];
+                    word[..bytes.len()].copy_from_slice(bytes);
+                    let word = &word[..len];
+                    let value = match endian {
+                        Endian::Little => word.iter().rev().fold(0, |v, b| v << 8 | *b as u64),
+                        Endian::Big => word.iter().fold(0, |v, b| v << 8 | *b as u64),
+                    };
+                    radix.write_cell(&mut output, value, len);
+                    cells_len += group_width(len);
+                }
+                None => {
+                    for (i, byte) in bytes.iter().enumerate() {
+                        if i > 0 {
+                            output.push_str(byte_sep);
+                        }
+                        if color {
+                            output.push_str(byte_color(*byte));
+                        }
+                        radix.write_cell(&mut output, *byte as u64, 1);
+                        if color {
+                            output.push_str(RESET);
+                        }
+                    }
+                    cells_len += group_width(bytes.len());
+                }
+            }
+        }
+        output.extend(std::iter::repeat_n(' ', cells_width - cells_len));
+
+        output.push_str("  ");
+        let row_start = width * index;
+        let chars = &column[row_start..row_start + row.len()];
+        if color {
+            for (byte, c) in row.iter().zip(chars) {
+                output.push_str(byte_color(*byte));
+                output.push(*c);
+                output.push_str(RESET);
+            }
+        } else {
+            output.extend(chars);
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_layout_is_formatted_correctly() {
+        let output = format_hexdump(9, b"0123456789abcdefXYZ", &Layout::default());
+
+        assert_eq!(
+            output.lines().collect::<Vec<_>>(),
+            [
+                "   9: 3031 3233 3435 3637 3839 6162 6364 6566  0123456789abcdef",
+                "  25: 5859 5a                                  XYZ",
+            ]
+        );
+    }
+
+    #[test]
+    fn uneven_group_is_formatted_correctly() {
+        let layout = Layout {
+            width: 6,
+            group: 4,
+            offset_radix: OffsetRadix::Hex,
+            ..Layout::default()
+        };
+
+        let output = format_hexdump(0x1f, b"\x00\x01\x02\x03\x04\x05\x06\x07", &layout);
+
+        assert_eq!(
+            output.lines().collect::<Vec<_>>(),
+            [
+                "0000001f: 00010203 0405  ......",
+                "00000025: 0607           ..",
+            ]
+        );
+    }
+
+    #[test]
+    fn repeated_rows_are_squeezed() {
+        let mut buffer = vec![0u8; 8];
+        buffer.extend([1; 4]);
+        buffer.extend([0; 4]);
+        buffer.extend([1; 8]);
+        buffer.extend([1; 2]);
+        let layout = Layout {
+            width: 4,
+            ..Layout::default()
+        };
+
+        let output = format_hexdump(0, &buffer, &layout);
+
+        assert_eq!(
+            output.lines().collect::<Vec<_>>(),
+            [
+                "   0: 0000 0000  ....",
+                "*",
+                "   8: 0101 0101  ....",
+                "  12: 0000 0000  ....",
+                "  16: 0101 0101  ....",
+                "*",
+                "  24: 0101       ..",
+            ]
+        );
+    }
+
+    #[test]
+    fn last_repeated_row_is_shown() {
+        let layout = Layout {
+            width: 2,
+            ..Layout::default()
+        };
+
+        let squeezed = format_hexdump(0, &[0; 6], &layout);
+        let unsqueezed = format_hexdump(
+            0,
+            &[0; 6],
+            &Layout {
+                squeeze: false,
+                ..layout
+            },
+        );
+
+        assert_eq!(squeezed, "   0: 0000  ..\n*\n   4: 0000  ..\n");
+        assert_eq!(unsqueezed.lines().count(), 3);
+    }
+
+    #[test]
+    fn other_radices_are_formatted_correctly() {
+        let layout = Layout {
+            width: 3,
+            ..Layout::default()
+        };
+        let bytes = b"\x08\xff\x00\x41";
+
+        let output = |radix| {
+            format_hexdump(
+                0,
+                bytes,
+                &Layout {
+                    radix,
+                    ..layout.clone()
+                },
+            )
+            .lines()
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            output(Radix::Octal),
+            ["   0:  010 377  000  ...", "   3:  101           A"]
+        );
+        assert_eq!(
+            output(Radix::Decimal),
+            ["   0:    8 255    0  ...", "   3:   65           A"]
+        );
+        assert_eq!(
+            output(Radix::Binary),
+            [
+                "   0:  00001000 11111111  00000000  ...",
+                "   3:  01000001                     A"
+            ]
+        );
+    }
+
+    #[test]
+    fn words_are_formatted_in_byte_order() {
+        let layout = Layout {
+            width: 8,
+            group: 4,
+            ..Layout::default()
+        };
+        let bytes = b"\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a";
+
+        let output = |words, radix| {
+            format_hexdump(
+                0,
+                bytes,
+                &Layout {
+                    words: Some(words),
+                    radix,
+                    ..layout.clone()
+                },
+            )
+            .lines()
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            output(Endian::Little, Radix::Hex),
+            [
+                "   0: 04030201 08070605  ........",
+                "   8: 00000a09           .."
+            ]
+        );
+        assert_eq!(
+            output(Endian::Big, Radix::Hex),
+            [
+                "   0: 01020304 05060708  ........",
+                "   8: 090a0000           .."
+            ]
+        );
+        assert_eq!(
+            output(Endian::Little, Radix::Decimal),
+            [
+                "   0:   67305985  134678021  ........",
+                "   8:       2569             .."
+            ]
+        );
+    }
+
+    #[test]
+    fn bytes_are_coloured_by_class() {
+        let layout = Layout {
+            width: 4,
+            color: true,
+            ..Layout::default()
+        };
+
+        let output = format_hexdump(0, b"\0A", &layout);
+
+        assert_eq!(
+            output,
+            "   0: \x1b[90m00\x1b[0m\x1b[36m41\x1b[0m       \x1b[90m.\x1b[0m\x1b[36mA\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn invalid_options_return_err() {
+        let inputs: &[(&[u8], &[u8])] = &[
+            (b"width", b"0"),
+            (b"width", b"257"),
+            (b"group", b"3"),
+            (b"offset", b"oct"),
+            (b"radix", b"b32"),
+            (b"words", b"on"),
+            (b"size", b"1"),
+        ];
+
+        for (name, value) in inputs {
+            assert!(LayoutOption::parse(name, value).is_err());
+        }
+    }
+}
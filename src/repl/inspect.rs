@@ -0,0 +1,166 @@
+//! The data inspector: every common interpretation of the bytes at a position.
+
+use std::{fmt::Write, net::Ipv4Addr};
+
+use crate::repl::{
+    display,
+    value::{Endian, ValueType},
+    varint,
+};
+
+/// The most bytes any interpretation looks at.
+pub const MAX_LEN: usize = 16;
+
+/// 9999-12-31 23:59:59 UTC.
+const MAX_TIMESTAMP: u64 = 253402300799;
+
+const NUMERIC_TYPES: &[&[u8]] = &[
+    b"u8", b"i8", b"u16", b"i16", b"u32", b"i32", b"u64", b"i64", b"f32", b"f64",
+];
+
+/// Returns a table of interpretations of `bytes`, one per line. Rows that
+/// need more bytes than there are show `-`.
+pub fn inspect(bytes: &[u8]) -> String {
+    let mut table = String::new();
+
+    let mut row = |name: &str, le: Option<String>, be: Option<String>| {
+        let le = le.unwrap_or("-".into());
+        match be {
+            Some(be) => writeln!(table, "{name:<8} {le:<28} {be}"),
+            None => writeln!(table, "{name:<8} {le}"),
+        }
+        .unwrap();
+    };
+
+    row("", Some("Little-endian".into()), Some("Big-endian".into()));
+
+    for name in NUMERIC_TYPES {
+        let le_name = [name, &b"le"[..]].concat();
+        let be_name = [name, &b"be"[..]].concat();
+        let le_type = ValueType::parse(&le_name).unwrap();
+        let be_type = ValueType::parse(&be_name).unwrap();
+
+        let value = |value_type: ValueType| {
+            bytes
+                .get(..value_type.width)
+                .map(|bytes| value_type.decimal(bytes))
+        };
+        let be_value = match be_type.width {
+            1 => None, // Same either way.
+            _ => Some(value(be_type).unwrap_or("-".into())),
+        };
+
+        row(str::from_utf8(name).unwrap(), value(le_type), be_value);
+    }
+
+    let with_len = |value: String, len: usize| {
+        let unit = if len == 1 { "byte" } else { "bytes" };
+        format!("{value} ({len} {unit})")
+    };
+    row(
+        "uleb128",
+        varint::decode_uleb128(bytes).map(|(value, len)| with_len(value.to_string(), len)),
+        None,
+    );
+    row(
+        "sleb128",
+        varint::decode_sleb128(bytes).map(|(value, len)| with_len(value.to_string(), len)),
+        None,
+    );
+
+    let time = |width: usize, endian: Endian| {
+        let bytes = bytes.get(..width)?;
+        let secs = match (width, endian) {
+            (4, Endian::Little) => i32::from_le_bytes(bytes.try_into().unwrap()) as i64,
+            (4, Endian::Big) => i32::from_be_bytes(bytes.try_into().unwrap()) as i64,
+            (_, Endian::Little) => i64::from_le_bytes(bytes.try_into().unwrap()),
+            (_, Endian::Big) => i64::from_be_bytes(bytes.try_into().unwrap()),
+        };
+        // Beyond year 9999 is more likely not a timestamp.
+        (secs.unsigned_abs() <= MAX_TIMESTAMP).then(|| display::format_unix_time(secs))
+    };
+    row(
+        "time32",
+        time(4, Endian::Little),
+        Some(time(4, Endian::Big).unwrap_or("-".into())),
+    );
+    row(
+        "time64",
+        time(8, Endian::Little),
+        Some(time(8, Endian::Big).unwrap_or("-".into())),
+    );
+
+    row("guid", bytes.get(..16).map(format_guid), None);
+    row(
+        "ipv4",
+        bytes
+            .get(..4)
+            .map(|b| Ipv4Addr::new(b[0], b[1], b[2], b[3]).to_string()),
+        None,
+    );
+
+    row(
+        "utf-8",
+        Some(format!("{:?}", String::from_utf8_lossy(bytes))),
+        None,
+    );
+    let utf16 = |endian: Endian| {
+        let units = bytes.chunks_exact(2).map(|pair| match endian {
+            Endian::Little => u16::from_le_bytes([pair[0], pair[1]]),
+            Endian::Big => u16::from_be_bytes([pair[0], pair[1]]),
+        });
+        let text: String = char::decode_utf16(units)
+            .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect();
+        format!("{text:?}")
+    };
+    row(
+        "utf-16",
+        Some(utf16(Endian::Little)),
+        Some(utf16(Endian::Big)),
+    );
+
+    table
+}
+
+/// Formats 16 bytes as a GUID, with the first three fields little-endian.
+fn format_guid(b: &[u8]) -> String {
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
+        u32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        u16::from_le_bytes([b[4], b[5]]),
+        u16::from_le_bytes([b[6], b[7]]),
+        b[8],
+        b[9],
+        b[10],
+        b[11],
+        b[12],
+        b[13],
+        b[14],
+        b[15]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_input_shows_dash_for_wide_types() {
+        let table = inspect(&[0x34, 0x12]);
+
+        assert!(table.contains(&format!("{:<8} {:<28} {}\n", "u16", "4660", "13330")));
+        assert!(table.contains(&format!("{:<8} {:<28} {}\n", "u32", "-", "-")));
+        assert!(table.contains(&format!("{:<8} {}\n", "guid", "-")));
+    }
+
+    #[test]
+    fn guid_is_formatted_correctly() {
+        let bytes = [
+            0x33, 0x22, 0x11, 0x00, 0x55, 0x44, 0x77, 0x66, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+            0xee, 0xff,
+        ];
+
+        assert_eq!(format_guid(&bytes), "00112233-4455-6677-8899-aabbccddeeff");
+    }
+}
@@ -1,6 +1,18 @@
 use std::{error::Error, io::SeekFrom};
 
-use crate::strings;
+use crate::{
+    repl::{
+        display::BitOrder,
+        encoding::Encoding,
+        export,
+        hexdump::LayoutOption,
+        records,
+        settings::Setting,
+        value::{Kind, ValueType, parse_integer, parse_signed_integer},
+        varint,
+    },
+    strings,
+};
 
 type ParseResult<T> = Result<T, Box<dyn Error>>;
 
@@ -8,12 +20,46 @@ type ParseResult<T> = Result<T, Box<dyn Error>>;
 pub struct ReadCommand {
     pub seek: SeekFrom,
     pub count: Option<usize>,
+    /// Write the bytes as they are, even if reads are being escaped.
+    pub raw: bool,
 }
 
 #[derive(Debug, PartialEq)]
 pub struct ReadbCommand {
     pub seek: SeekFrom,
     pub count: Option<usize>,
+    pub options: Vec<LayoutOption>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ReadiCommand {
+    pub seek: SeekFrom,
+    pub value_type: ValueType,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ReadvCommand {
+    pub seek: SeekFrom,
+    pub format: varint::Format,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ReadbitsCommand {
+    pub seek: SeekFrom,
+    pub count: usize,
+    pub order: BitOrder,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ReadsCommand {
+    pub seek: SeekFrom,
+    pub max: Option<usize>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ReadpCommand {
+    pub seek: SeekFrom,
+    pub prefix: ValueType,
 }
 
 #[derive(Debug, PartialEq)]
@@ -28,16 +74,188 @@ pub struct WritebCommand {
     pub bytes: Vec<u8>,
 }
 
+#[derive(Debug, PartialEq)]
+pub struct WriteencCommand {
+    pub seek: SeekFrom,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct WriteencFileCommand {
+    pub seek: SeekFrom,
+    pub encoding: Encoding,
+    pub path: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct WriteiCommand {
+    pub seek: SeekFrom,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct WritevCommand {
+    pub seek: SeekFrom,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BitsCommand {
+    pub seek: SeekFrom,
+    pub mask: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TransformCommand {
+    pub range: Range,
+    pub op: TransformOp,
+    pub key: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransformOp {
+    Xor,
+    And,
+    Or,
+    Not,
+    Add,
+    Sub,
+    Rol,
+    Ror,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SwapCommand {
+    pub range: Range,
+    pub width: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ReverseCommand(pub Range);
+
 #[derive(Debug, PartialEq)]
 pub struct SeekCommand(pub SeekFrom);
 
+#[derive(Debug, PartialEq)]
+pub struct FillCommand {
+    pub range: Range,
+    pub pattern: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FillbCommand {
+    pub range: Range,
+    pub pattern: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InsertCommand {
+    pub seek: SeekFrom,
+    pub index: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InsertbCommand {
+    pub seek: SeekFrom,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DeleteCommand(pub Range);
+
+#[derive(Debug, PartialEq)]
+pub struct TruncateCommand(pub SeekFrom);
+
+#[derive(Debug, PartialEq)]
+pub struct InspectCommand(pub SeekFrom);
+
+#[derive(Debug, PartialEq)]
+pub enum TemplateCommand {
+    Load(String),
+    List,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DecodeCommand {
+    pub seek: SeekFrom,
+    pub name: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DecodeArrayCommand {
+    pub seek: SeekFrom,
+    pub name: String,
+    pub count: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SetCommand {
+    pub seek: SeekFrom,
+    pub name: String,
+    pub field: String,
+    pub value: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DumpCommand {
+    pub range: Range,
+    pub format: export::Format,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct LoadCommand {
+    pub format: records::Format,
+    pub path: String,
+    /// Added to the address of each record.
+    pub base: i64,
+}
+
+/// A span of bytes starting at `seek`. If `count` is `None`, the span extends
+/// to the end of the file.
+#[derive(Debug, PartialEq)]
+pub struct Range {
+    pub seek: SeekFrom,
+    pub count: Option<u64>,
+}
+
 #[derive(Debug, PartialEq)]
 pub enum Command {
     Read(ReadCommand),
     Readb(ReadbCommand),
+    Readi(ReadiCommand),
+    Readv(ReadvCommand),
+    Readbits(ReadbitsCommand),
+    Reads(ReadsCommand),
+    Readp(ReadpCommand),
     Write(WriteCommand),
     Writeb(WritebCommand),
+    Writeenc(WriteencCommand),
+    WriteencFile(WriteencFileCommand),
+    Writei(WriteiCommand),
+    Writev(WritevCommand),
+    Setbits(BitsCommand),
+    Clearbits(BitsCommand),
+    Togglebits(BitsCommand),
+    Transform(TransformCommand),
+    Swap(SwapCommand),
+    Reverse(ReverseCommand),
     Seek(SeekCommand),
+    Fill(FillCommand),
+    Fillb(FillbCommand),
+    Insert(InsertCommand),
+    Insertb(InsertbCommand),
+    Delete(DeleteCommand),
+    Truncate(TruncateCommand),
+    Dump(DumpCommand),
+    Load(LoadCommand),
+    Inspect(InspectCommand),
+    Template(TemplateCommand),
+    Decode(DecodeCommand),
+    DecodeArray(DecodeArrayCommand),
+    Set(SetCommand),
+    /// Change a setting, or list them if `None`.
+    Config(Option<Setting>),
+    Info,
     Help,
     Quit,
     Nop,
@@ -45,16 +263,54 @@ pub enum Command {
 
 const OP_READ: &[u8] = b"read";
 const OP_READB: &[u8] = b"readb";
+const OP_READI: &[u8] = b"readi";
+const OP_READV: &[u8] = b"readv";
+const OP_READBITS: &[u8] = b"readbits";
+const OP_READS: &[u8] = b"reads";
+const OP_READP: &[u8] = b"readp";
 const OP_WRITE: &[u8] = b"write";
 const OP_WRITEB: &[u8] = b"writeb";
+const OP_WRITEENC: &[u8] = b"writeenc";
+const OP_WRITEENC_FILE: &[u8] = b"writeenc-file";
+const OP_WRITEI: &[u8] = b"writei";
+const OP_WRITEV: &[u8] = b"writev";
+const OP_SETBITS: &[u8] = b"setbits";
+const OP_CLEARBITS: &[u8] = b"clearbits";
+const OP_TOGGLEBITS: &[u8] = b"togglebits";
+const OP_TRANSFORM: &[u8] = b"transform";
+const OP_SWAP: &[u8] = b"swap";
+const OP_REVERSE: &[u8] = b"reverse";
 const OP_SEEK: &[u8] = b"seek";
+const OP_FILL: &[u8] = b"fill";
+const OP_FILLB: &[u8] = b"fillb";
+const OP_INSERT: &[u8] = b"insert";
+const OP_INSERTB: &[u8] = b"insertb";
+const OP_DELETE: &[u8] = b"delete";
+const OP_TRUNCATE: &[u8] = b"truncate";
+const OP_RESIZE: &[u8] = b"resize";
+const OP_DUMP: &[u8] = b"dump";
+const OP_LOAD_IHEX: &[u8] = b"load-ihex";
+const OP_LOAD_SREC: &[u8] = b"load-srec";
+const OP_INSPECT: &[u8] = b"inspect";
+const OP_TEMPLATE: &[u8] = b"template";
+const OP_DECODE: &[u8] = b"decode";
+const OP_DECODE_ARRAY: &[u8] = b"decode-array";
+const OP_SET: &[u8] = b"set";
+const OP_INFO: &[u8] = b"info";
+const OP_STAT: &[u8] = b"stat";
 const OP_HELP: &[u8] = b"help";
 const OP_QUIT: &[u8] = b"quit";
 // Short forms
 const OP_R: &[u8] = b"r";
+const OP_READ_RAW: &[u8] = b"read!";
+const OP_R_RAW: &[u8] = b"r!";
 const OP_RB: &[u8] = b"rb";
+const OP_RI: &[u8] = b"ri";
+const OP_RV: &[u8] = b"rv";
 const OP_W: &[u8] = b"w";
 const OP_WB: &[u8] = b"wb";
+const OP_WI: &[u8] = b"wi";
+const OP_WV: &[u8] = b"wv";
 const OP_S: &[u8] = b"s";
 const OP_H: &[u8] = b"h";
 const OP_Q: &[u8] = b"q";
@@ -71,10 +327,50 @@ pub fn parse_input(input: &[u8]) -> ParseResult<Command> {
 
     let command = match op.to_ascii_lowercase().as_slice() {
         OP_READ | OP_R => Command::Read(parse_read_command(input_words)?),
+        OP_READ_RAW | OP_R_RAW => Command::Read(ReadCommand {
+            raw: true,
+            ..parse_read_command(input_words)?
+        }),
         OP_READB | OP_RB => Command::Readb(parse_readb_command(input_words)?),
+        OP_READI | OP_RI => Command::Readi(parse_readi_command(input_words)?),
+        OP_READV | OP_RV => Command::Readv(parse_readv_command(input_words)?),
+        OP_READBITS => Command::Readbits(parse_readbits_command(input_words)?),
+        OP_READS => Command::Reads(parse_reads_command(input_words)?),
+        OP_READP => Command::Readp(parse_readp_command(input_words)?),
         OP_WRITE | OP_W => Command::Write(parse_write_command(input_words, input)?),
         OP_WRITEB | OP_WB => Command::Writeb(parse_writeb_command(input_words)?),
+        OP_WRITEENC => Command::Writeenc(parse_writeenc_command(input_words, input)?),
+        OP_WRITEENC_FILE => Command::WriteencFile(parse_writeenc_file_command(input_words, input)?),
+        OP_WRITEI | OP_WI => Command::Writei(parse_writei_command(input_words)?),
+        OP_WRITEV | OP_WV => Command::Writev(parse_writev_command(input_words)?),
+        OP_SETBITS => Command::Setbits(parse_bits_command(input_words)?),
+        OP_CLEARBITS => Command::Clearbits(parse_bits_command(input_words)?),
+        OP_TOGGLEBITS => Command::Togglebits(parse_bits_command(input_words)?),
+        OP_TRANSFORM => Command::Transform(parse_transform_command(input_words)?),
+        OP_SWAP => Command::Swap(parse_swap_command(input_words)?),
+        OP_REVERSE => Command::Reverse(parse_reverse_command(input_words)?),
         OP_SEEK | OP_S => Command::Seek(parse_seek_command(input_words)?),
+        OP_FILL => Command::Fill(parse_fill_command(input_words, input)?),
+        OP_FILLB => Command::Fillb(parse_fillb_command(input_words)?),
+        OP_INSERT => Command::Insert(parse_insert_command(input_words, input)?),
+        OP_INSERTB => Command::Insertb(parse_insertb_command(input_words)?),
+        OP_DELETE => Command::Delete(parse_delete_command(input_words)?),
+        OP_TRUNCATE | OP_RESIZE => Command::Truncate(parse_truncate_command(input_words)?),
+        OP_DUMP => Command::Dump(parse_dump_command(input_words)?),
+        OP_LOAD_IHEX => Command::Load(parse_load_command(input, records::Format::Ihex)?),
+        OP_LOAD_SREC => Command::Load(parse_load_command(input, records::Format::Srec)?),
+        OP_INSPECT => Command::Inspect(parse_inspect_command(input_words)?),
+        OP_TEMPLATE => Command::Template(parse_template_command(input_words, input)?),
+        OP_DECODE => Command::Decode(parse_decode_command(input_words)?),
+        OP_DECODE_ARRAY => Command::DecodeArray(parse_decode_array_command(input_words)?),
+        OP_SET => match input_words.clone().next() {
+            None => Command::Config(None),
+            Some([c, ..]) if c.is_ascii_alphabetic() => {
+                Command::Config(Some(parse_setting_command(input_words)?))
+            }
+            Some(_) => Command::Set(parse_set_command(input_words, input)?),
+        },
+        OP_INFO | OP_STAT => Command::Info,
         OP_HELP | OP_H => Command::Help,
         OP_QUIT | OP_Q => Command::Quit,
         _ => return Err(strings::UNRECOGNIZED_COMMAND)?,
@@ -87,24 +383,116 @@ fn parse_read_command<'a>(mut args: impl Iterator<Item = &'a [u8]>) -> ParseResu
     let seek_arg = args.next().ok_or(strings::MISSING_SEEK_ARG)?;
     let seek = parse_seek_arg(seek_arg)?;
 
-    let count_arg = args.next().map(String::from_utf8_lossy);
-    let count = match count_arg {
+    let count = match args.next() {
         None => None,
         Some(c) => {
-            let num = c
-                .parse::<usize>()
-                .map_err(|_| strings::INVALID_DIGIT_IN_COUNT_ARG)?;
-            Some(num)
+            let num = parse_count_arg(c)?;
+            Some(usize::try_from(num).map_err(|_| strings::INVALID_DIGIT_IN_COUNT_ARG)?)
         }
     };
 
-    Ok(ReadCommand { seek, count })
+    Ok(ReadCommand {
+        seek,
+        count,
+        raw: false,
+    })
 }
 
-fn parse_readb_command<'a>(args: impl Iterator<Item = &'a [u8]>) -> ParseResult<ReadbCommand> {
-    let ReadCommand { seek, count } = parse_read_command(args)?;
+fn parse_readb_command<'a>(mut args: impl Iterator<Item = &'a [u8]>) -> ParseResult<ReadbCommand> {
+    let seek_arg = args.next().ok_or(strings::MISSING_SEEK_ARG)?;
+    let seek = parse_seek_arg(seek_arg)?;
 
-    Ok(ReadbCommand { seek, count })
+    let mut count = None;
+    let mut options = Vec::new();
+
+    // Layout options like `width=8` can go anywhere after the seek argument.
+    for arg in args {
+        match arg.iter().position(|&c| c == b'=') {
+            Some(i) => options.push(LayoutOption::parse(&arg[..i], &arg[i + 1..])?),
+            None if count.is_none() => {
+                let num = parse_count_arg(arg)?;
+                count =
+                    Some(usize::try_from(num).map_err(|_| strings::INVALID_DIGIT_IN_COUNT_ARG)?);
+            }
+            None => Err(strings::UNEXPECTED_ARG)?,
+        }
+    }
+
+    Ok(ReadbCommand {
+        seek,
+        count,
+        options,
+    })
+}
+
+fn parse_readi_command<'a>(mut args: impl Iterator<Item = &'a [u8]>) -> ParseResult<ReadiCommand> {
+    let seek_arg = args.next().ok_or(strings::MISSING_SEEK_ARG)?;
+    let seek = parse_seek_arg(seek_arg)?;
+
+    let value_type = parse_type_arg(args.next())?;
+
+    Ok(ReadiCommand { seek, value_type })
+}
+
+fn parse_type_arg(word: Option<&[u8]>) -> ParseResult<ValueType> {
+    let word = word.ok_or(strings::MISSING_TYPE_ARG)?;
+
+    Ok(ValueType::parse(word).ok_or(strings::INVALID_TYPE_ARG)?)
+}
+
+fn parse_readv_command<'a>(mut args: impl Iterator<Item = &'a [u8]>) -> ParseResult<ReadvCommand> {
+    let seek_arg = args.next().ok_or(strings::MISSING_SEEK_ARG)?;
+    let seek = parse_seek_arg(seek_arg)?;
+
+    let format = parse_varint_format_arg(args.next())?;
+
+    Ok(ReadvCommand { seek, format })
+}
+
+fn parse_varint_format_arg(word: Option<&[u8]>) -> ParseResult<varint::Format> {
+    let word = word.ok_or(strings::MISSING_FORMAT_ARG)?;
+
+    Ok(varint::Format::parse(word).ok_or(strings::INVALID_FORMAT_ARG)?)
+}
+
+const ORDER_MSB: &[u8] = b"msb";
+const ORDER_LSB: &[u8] = b"lsb";
+
+fn parse_readbits_command<'a>(
+    mut args: impl Iterator<Item = &'a [u8]>,
+) -> ParseResult<ReadbitsCommand> {
+    let seek_arg = args.next().ok_or(strings::MISSING_SEEK_ARG)?;
+    let seek = parse_seek_arg(seek_arg)?;
+
+    let count_arg = args.next().ok_or(strings::MISSING_COUNT_ARG)?;
+    let count = usize::try_from(parse_count_arg(count_arg)?)
+        .map_err(|_| strings::INVALID_DIGIT_IN_COUNT_ARG)?;
+
+    let order = match args.next().map(|arg| arg.to_ascii_lowercase()).as_deref() {
+        None | Some(ORDER_MSB) => BitOrder::MsbFirst,
+        Some(ORDER_LSB) => BitOrder::LsbFirst,
+        Some(_) => return Err(strings::INVALID_ORDER_ARG.into()),
+    };
+
+    Ok(ReadbitsCommand { seek, count, order })
+}
+
+fn parse_reads_command<'a>(args: impl Iterator<Item = &'a [u8]>) -> ParseResult<ReadsCommand> {
+    let ReadCommand { seek, count, .. } = parse_read_command(args)?;
+
+    Ok(ReadsCommand { seek, max: count })
+}
+
+fn parse_readp_command<'a>(mut args: impl Iterator<Item = &'a [u8]>) -> ParseResult<ReadpCommand> {
+    let seek_arg = args.next().ok_or(strings::MISSING_SEEK_ARG)?;
+    let seek = parse_seek_arg(seek_arg)?;
+
+    let prefix = parse_type_arg(args.next())?;
+    if prefix.kind != Kind::Unsigned {
+        return Err(strings::INVALID_PREFIX_TYPE_ARG.into());
+    }
+
+    Ok(ReadpCommand { seek, prefix })
 }
 
 fn parse_write_command<'a>(
@@ -114,6 +502,15 @@ fn parse_write_command<'a>(
     let seek_arg = args.next().ok_or(strings::MISSING_SEEK_ARG)?;
     let seek = parse_seek_arg(seek_arg)?;
 
+    Ok(WriteCommand {
+        seek,
+        index: contents_index(command_line, 2),
+    })
+}
+
+/// Returns the char index of the first non-whitespace character after the
+/// first `skip_words` words of `command_line`, or its length if there is none.
+fn contents_index(command_line: &[u8], skip_words: usize) -> usize {
     // Enumerate space-separated "words". Each whitespace character has two
     // "words" on either side, which could be 0 length.
     // E.g., "  write " => (0, b""), (1, b""), (2, b"write"), (3, b"")
@@ -123,73 +520,422 @@ fn parse_write_command<'a>(
         .enumerate()
         .filter(|(_, chunk)| !chunk.is_empty());
 
-    // len(op + seek)
-    let op_n_seek_len = cmd_words
+    // len(skipped words), e.g. len(op + seek)
+    let skipped_len = cmd_words
         .by_ref()
-        .take(2)
+        .take(skip_words)
         .fold(0, |acc, (_, chunk)| acc + chunk.len());
 
-    // Char index of first valid character in write contents.
-    let write_buf_start = match cmd_words.next() {
-        Some((i, _)) => op_n_seek_len + i,
+    match cmd_words.next() {
+        Some((i, _)) => skipped_len + i,
         None => command_line.len(),
+    }
+}
+
+fn parse_writeb_command<'a>(
+    mut args: impl Iterator<Item = &'a [u8]>,
+) -> ParseResult<WritebCommand> {
+    let seek_arg = args.next().ok_or(strings::MISSING_SEEK_ARG)?;
+    let seek = parse_seek_arg(seek_arg)?;
+
+    let bytes = parse_hex_bytes(args)?;
+
+    Ok(WritebCommand { seek, bytes })
+}
+
+fn parse_writeenc_command<'a>(
+    mut args: impl Iterator<Item = &'a [u8]>,
+    command_line: &[u8],
+) -> ParseResult<WriteencCommand> {
+    let seek_arg = args.next().ok_or(strings::MISSING_SEEK_ARG)?;
+    let seek = parse_seek_arg(seek_arg)?;
+
+    let encoding = parse_encoding_arg(args.next())?;
+    let bytes = encoding.decode(&command_line[contents_index(command_line, 3)..])?;
+
+    Ok(WriteencCommand { seek, bytes })
+}
+
+fn parse_writeenc_file_command<'a>(
+    mut args: impl Iterator<Item = &'a [u8]>,
+    command_line: &[u8],
+) -> ParseResult<WriteencFileCommand> {
+    let seek_arg = args.next().ok_or(strings::MISSING_SEEK_ARG)?;
+    let seek = parse_seek_arg(seek_arg)?;
+
+    let encoding = parse_encoding_arg(args.next())?;
+
+    let path = &command_line[contents_index(command_line, 3)..];
+    if path.is_empty() {
+        return Err(strings::MISSING_PATH_ARG.into());
+    }
+
+    Ok(WriteencFileCommand {
+        seek,
+        encoding,
+        path: String::from_utf8_lossy(path).trim_end().to_string(),
+    })
+}
+
+fn parse_encoding_arg(word: Option<&[u8]>) -> ParseResult<Encoding> {
+    let word = word.ok_or(strings::MISSING_ENCODING_ARG)?;
+
+    Ok(Encoding::parse(word).ok_or(strings::INVALID_ENCODING_ARG)?)
+}
+
+fn parse_writei_command<'a>(
+    mut args: impl Iterator<Item = &'a [u8]>,
+) -> ParseResult<WriteiCommand> {
+    let seek_arg = args.next().ok_or(strings::MISSING_SEEK_ARG)?;
+    let seek = parse_seek_arg(seek_arg)?;
+
+    let value_type = parse_type_arg(args.next())?;
+
+    let value_arg = args.next().ok_or(strings::MISSING_VALUE_ARG)?;
+    let bytes = value_type.encode(value_arg)?;
+
+    Ok(WriteiCommand { seek, bytes })
+}
+
+fn parse_writev_command<'a>(
+    mut args: impl Iterator<Item = &'a [u8]>,
+) -> ParseResult<WritevCommand> {
+    let seek_arg = args.next().ok_or(strings::MISSING_SEEK_ARG)?;
+    let seek = parse_seek_arg(seek_arg)?;
+
+    let format = parse_varint_format_arg(args.next())?;
+
+    let value_arg = args.next().ok_or(strings::MISSING_VALUE_ARG)?;
+    let bytes = format.encode(value_arg).ok_or(strings::INVALID_VALUE_ARG)?;
+
+    Ok(WritevCommand { seek, bytes })
+}
+
+fn parse_bits_command<'a>(mut args: impl Iterator<Item = &'a [u8]>) -> ParseResult<BitsCommand> {
+    let WritebCommand { seek, bytes } = parse_writeb_command(args.by_ref())?;
+    if bytes.is_empty() {
+        return Err(strings::MISSING_MASK_ARG.into());
+    }
+
+    Ok(BitsCommand { seek, mask: bytes })
+}
+
+fn parse_transform_command<'a>(
+    mut args: impl Iterator<Item = &'a [u8]>,
+) -> ParseResult<TransformCommand> {
+    let range_arg = args.next().ok_or(strings::MISSING_RANGE_ARG)?;
+    let range = parse_range_arg(range_arg)?;
+
+    let op_arg = args.next().ok_or(strings::MISSING_OPERATION_ARG)?;
+    let op = match op_arg.to_ascii_lowercase().as_slice() {
+        b"xor" => TransformOp::Xor,
+        b"and" => TransformOp::And,
+        b"or" => TransformOp::Or,
+        b"not" => TransformOp::Not,
+        b"add" => TransformOp::Add,
+        b"sub" => TransformOp::Sub,
+        b"rol" => TransformOp::Rol,
+        b"ror" => TransformOp::Ror,
+        _ => return Err(strings::INVALID_OPERATION_ARG.into()),
+    };
+
+    let key = parse_hex_bytes(args)?;
+    match (op, key.is_empty()) {
+        (TransformOp::Not, false) => return Err(strings::UNEXPECTED_KEY_ARG.into()),
+        (TransformOp::Not, true) => {}
+        (_, true) => return Err(strings::MISSING_KEY_ARG.into()),
+        (_, false) => {}
+    }
+
+    Ok(TransformCommand { range, op, key })
+}
+
+fn parse_swap_command<'a>(mut args: impl Iterator<Item = &'a [u8]>) -> ParseResult<SwapCommand> {
+    let range_arg = args.next().ok_or(strings::MISSING_RANGE_ARG)?;
+    let range = parse_range_arg(range_arg)?;
+
+    let width_arg = args.next().ok_or(strings::MISSING_WIDTH_ARG)?;
+    let width = match width_arg {
+        b"2" => 2,
+        b"4" => 4,
+        b"8" => 8,
+        _ => return Err(strings::INVALID_WIDTH_ARG.into()),
+    };
+
+    Ok(SwapCommand { range, width })
+}
+
+fn parse_reverse_command<'a>(
+    mut args: impl Iterator<Item = &'a [u8]>,
+) -> ParseResult<ReverseCommand> {
+    let range_arg = args.next().ok_or(strings::MISSING_RANGE_ARG)?;
+    let range = parse_range_arg(range_arg)?;
+
+    Ok(ReverseCommand(range))
+}
+
+fn parse_dump_command<'a>(mut args: impl Iterator<Item = &'a [u8]>) -> ParseResult<DumpCommand> {
+    let range_arg = args.next().ok_or(strings::MISSING_RANGE_ARG)?;
+    let range = parse_range_arg(range_arg)?;
+
+    let format_arg = args.next().ok_or(strings::MISSING_FORMAT_ARG)?;
+    let format = export::Format::parse(format_arg).ok_or(strings::INVALID_FORMAT_ARG)?;
+
+    Ok(DumpCommand { range, format })
+}
+
+fn parse_load_command(command_line: &[u8], format: records::Format) -> ParseResult<LoadCommand> {
+    let rest = command_line[contents_index(command_line, 1)..].trim_ascii_end();
+    if rest.is_empty() {
+        return Err(strings::MISSING_PATH_ARG.into());
+    }
+
+    // The path can contain spaces, so a last word is only the base if it is a
+    // number.
+    let (path, base) = match rest.iter().rposition(u8::is_ascii_whitespace) {
+        Some(i) => match parse_signed_integer(&rest[i + 1..]) {
+            Some(base) => (rest[..i].trim_ascii_end(), base),
+            None => (rest, 0),
+        },
+        None => (rest, 0),
     };
 
-    Ok(WriteCommand {
-        seek,
-        index: write_buf_start,
-    })
+    Ok(LoadCommand {
+        format,
+        path: String::from_utf8_lossy(path).to_string(),
+        base,
+    })
+}
+
+fn parse_hex_bytes<'a>(args: impl Iterator<Item = &'a [u8]>) -> ParseResult<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::with_capacity(1024);
+
+    let byte_args = args.map(String::from_utf8_lossy);
+
+    for byte_arg in byte_args {
+        // TODO: use u8::from_ascii_radix once stable
+        let byte = u8::from_str_radix(&byte_arg, 16).map_err(|_| strings::INVALID_BYTE_ARG)?;
+        bytes.push(byte);
+    }
+
+    Ok(bytes)
+}
+
+fn parse_seek_command<'a>(mut args: impl Iterator<Item = &'a [u8]>) -> ParseResult<SeekCommand> {
+    let seek_arg = args.next().ok_or(strings::MISSING_SEEK_ARG)?;
+    let seek = parse_seek_arg(seek_arg)?;
+
+    Ok(SeekCommand(seek))
+}
+
+fn parse_fill_command<'a>(
+    mut args: impl Iterator<Item = &'a [u8]>,
+    command_line: &[u8],
+) -> ParseResult<FillCommand> {
+    let range_arg = args.next().ok_or(strings::MISSING_RANGE_ARG)?;
+    let range = parse_range_arg(range_arg)?;
+
+    let pattern = command_line[contents_index(command_line, 2)..].to_vec();
+    if pattern.is_empty() {
+        return Err(strings::MISSING_PATTERN_ARG.into());
+    }
+
+    Ok(FillCommand { range, pattern })
+}
+
+fn parse_fillb_command<'a>(mut args: impl Iterator<Item = &'a [u8]>) -> ParseResult<FillbCommand> {
+    let range_arg = args.next().ok_or(strings::MISSING_RANGE_ARG)?;
+    let range = parse_range_arg(range_arg)?;
+
+    let pattern = parse_hex_bytes(args)?;
+    if pattern.is_empty() {
+        return Err(strings::MISSING_PATTERN_ARG.into());
+    }
+
+    Ok(FillbCommand { range, pattern })
+}
+
+fn parse_insert_command<'a>(
+    args: impl Iterator<Item = &'a [u8]>,
+    command_line: &[u8],
+) -> ParseResult<InsertCommand> {
+    let WriteCommand { seek, index } = parse_write_command(args, command_line)?;
+
+    Ok(InsertCommand { seek, index })
+}
+
+fn parse_insertb_command<'a>(args: impl Iterator<Item = &'a [u8]>) -> ParseResult<InsertbCommand> {
+    let WritebCommand { seek, bytes } = parse_writeb_command(args)?;
+    if bytes.is_empty() {
+        return Err(strings::MISSING_BYTES_ARG.into());
+    }
+
+    Ok(InsertbCommand { seek, bytes })
+}
+
+fn parse_delete_command<'a>(
+    mut args: impl Iterator<Item = &'a [u8]>,
+) -> ParseResult<DeleteCommand> {
+    let range_arg = args.next().ok_or(strings::MISSING_RANGE_ARG)?;
+    let range = parse_range_arg(range_arg)?;
+
+    Ok(DeleteCommand(range))
+}
+
+fn parse_truncate_command<'a>(
+    mut args: impl Iterator<Item = &'a [u8]>,
+) -> ParseResult<TruncateCommand> {
+    let size_arg = args.next().ok_or(strings::MISSING_SIZE_ARG)?;
+    let size = parse_seek_arg(size_arg)?;
+
+    Ok(TruncateCommand(size))
+}
+
+fn parse_inspect_command<'a>(
+    mut args: impl Iterator<Item = &'a [u8]>,
+) -> ParseResult<InspectCommand> {
+    let seek = match args.next() {
+        Some(seek_arg) => parse_seek_arg(seek_arg)?,
+        None => SeekFrom::Current(0),
+    };
+
+    Ok(InspectCommand(seek))
+}
+
+const SUBCMD_LOAD: &[u8] = b"load";
+const SUBCMD_LIST: &[u8] = b"list";
+
+fn parse_template_command<'a>(
+    mut args: impl Iterator<Item = &'a [u8]>,
+    command_line: &[u8],
+) -> ParseResult<TemplateCommand> {
+    let subcommand = args.next().ok_or(strings::MISSING_SUBCOMMAND)?;
+
+    match subcommand.to_ascii_lowercase().as_slice() {
+        SUBCMD_LOAD => {
+            let path = &command_line[contents_index(command_line, 2)..];
+            if path.is_empty() {
+                return Err(strings::MISSING_PATH_ARG.into());
+            }
+            Ok(TemplateCommand::Load(
+                String::from_utf8_lossy(path).trim_end().to_string(),
+            ))
+        }
+        SUBCMD_LIST => Ok(TemplateCommand::List),
+        _ => Err(strings::INVALID_SUBCOMMAND)?,
+    }
+}
+
+fn parse_decode_command<'a>(
+    mut args: impl Iterator<Item = &'a [u8]>,
+) -> ParseResult<DecodeCommand> {
+    let seek_arg = args.next().ok_or(strings::MISSING_SEEK_ARG)?;
+    let seek = parse_seek_arg(seek_arg)?;
+
+    let name_arg = args.next().ok_or(strings::MISSING_STRUCT_ARG)?;
+    let name = String::from_utf8_lossy(name_arg).into_owned();
+
+    Ok(DecodeCommand { seek, name })
+}
+
+fn parse_decode_array_command<'a>(
+    mut args: impl Iterator<Item = &'a [u8]>,
+) -> ParseResult<DecodeArrayCommand> {
+    let DecodeCommand { seek, name } = parse_decode_command(args.by_ref())?;
+
+    let count_arg = args.next().ok_or(strings::MISSING_COUNT_ARG)?;
+    let count = usize::try_from(parse_count_arg(count_arg)?)
+        .map_err(|_| strings::INVALID_DIGIT_IN_COUNT_ARG)?;
+
+    Ok(DecodeArrayCommand { seek, name, count })
 }
 
-fn parse_writeb_command<'a>(
+fn parse_set_command<'a>(
     mut args: impl Iterator<Item = &'a [u8]>,
-) -> ParseResult<WritebCommand> {
+    command_line: &[u8],
+) -> ParseResult<SetCommand> {
     let seek_arg = args.next().ok_or(strings::MISSING_SEEK_ARG)?;
     let seek = parse_seek_arg(seek_arg)?;
 
-    let mut bytes: Vec<u8> = Vec::with_capacity(1024);
-
-    let byte_args = args.map(String::from_utf8_lossy);
+    let field_arg = args.next().ok_or(strings::MISSING_FIELD_ARG)?;
+    let field_arg = String::from_utf8_lossy(field_arg);
+    let (name, field) = field_arg
+        .split_once('.')
+        .filter(|(name, field)| !name.is_empty() && !field.is_empty())
+        .ok_or(strings::MISSING_FIELD_ARG)?;
 
-    for byte_arg in byte_args {
-        // TODO: use u8::from_ascii_radix once stable
-        let byte = u8::from_str_radix(&byte_arg, 16).map_err(|_| strings::INVALID_BYTE_ARG)?;
-        bytes.push(byte);
+    // Text fields can take whitespace.
+    let value = command_line[contents_index(command_line, 3)..].to_vec();
+    if value.is_empty() {
+        return Err(strings::MISSING_VALUE_ARG.into());
     }
 
-    Ok(WritebCommand { seek, bytes })
+    Ok(SetCommand {
+        seek,
+        name: name.to_string(),
+        field: field.to_string(),
+        value,
+    })
 }
 
-fn parse_seek_command<'a>(mut args: impl Iterator<Item = &'a [u8]>) -> ParseResult<SeekCommand> {
-    let seek_arg = args.next().ok_or(strings::MISSING_SEEK_ARG)?;
+fn parse_setting_command<'a>(mut args: impl Iterator<Item = &'a [u8]>) -> ParseResult<Setting> {
+    let name_arg = args.next().ok_or(strings::UNKNOWN_SETTING)?;
+    let value_arg = args.next().ok_or(strings::MISSING_VALUE_ARG)?;
+
+    Ok(Setting::parse(name_arg, value_arg)?)
+}
+
+fn parse_range_arg(word: &[u8]) -> ParseResult<Range> {
+    let (seek_arg, count_arg) = match word.iter().position(|&c| c == b':') {
+        Some(i) => (&word[..i], Some(&word[i + 1..])),
+        None => (word, None),
+    };
+
     let seek = parse_seek_arg(seek_arg)?;
+    let count = count_arg.map(parse_count_arg).transpose()?;
 
-    Ok(SeekCommand(seek))
+    Ok(Range { seek, count })
+}
+
+fn parse_count_arg(word: &[u8]) -> ParseResult<u64> {
+    Ok(parse_integer(word).ok_or(strings::INVALID_DIGIT_IN_COUNT_ARG)?)
 }
 
 fn parse_seek_arg(word: &[u8]) -> Result<SeekFrom, Box<dyn Error>> {
     match word {
         [] => Err(strings::MISSING_SEEK_ARG)?,
         [b'.'] => Ok(SeekFrom::Current(0)),
-        num @ [b'+' | b'-', ..] => {
-            let num = String::from_utf8_lossy(num)
-                .parse()
-                .map_err(|_| strings::INVALID_DIGIT_IN_SEEK_ARG)?;
-            Ok(SeekFrom::Current(num))
+        [sign @ (b'+' | b'-'), num @ .., b'<'] => {
+            let num = parse_integer(num).ok_or(strings::INVALID_DIGIT_IN_SEEK_ARG)?;
+            let num = match sign {
+                b'+' => i64::try_from(num).ok(),
+                _ => 0i64.checked_sub_unsigned(num),
+            };
+            Ok(SeekFrom::End(
+                num.ok_or(strings::INVALID_DIGIT_IN_SEEK_ARG)?,
+            ))
+        }
+        [sign @ (b'+' | b'-'), num @ ..] => {
+            let num = parse_integer(num).ok_or(strings::INVALID_DIGIT_IN_SEEK_ARG)?;
+            let num = match sign {
+                b'+' => i64::try_from(num).ok(),
+                _ => 0i64.checked_sub_unsigned(num),
+            };
+            Ok(SeekFrom::Current(
+                num.ok_or(strings::INVALID_DIGIT_IN_SEEK_ARG)?,
+            ))
         }
         [num @ .., b'<'] => match num {
             [] => Ok(SeekFrom::End(0)),
             _ => {
-                let num: i64 = String::from_utf8_lossy(num)
-                    .parse()
-                    .map_err(|_| strings::INVALID_DIGIT_IN_SEEK_ARG)?;
-                Ok(SeekFrom::End(0 - num))
+                let num = parse_integer(num)
+                    .and_then(|num| 0i64.checked_sub_unsigned(num))
+                    .ok_or(strings::INVALID_DIGIT_IN_SEEK_ARG)?;
+                Ok(SeekFrom::End(num))
             }
         },
-        num @ _ => {
-            let num = String::from_utf8_lossy(num)
-                .parse()
-                .map_err(|_| strings::INVALID_SEEK_ARG)?;
+        num => {
+            let num = parse_integer(num).ok_or(strings::INVALID_SEEK_ARG)?;
             Ok(SeekFrom::Start(num))
         }
     }
@@ -209,7 +955,14 @@ mod tests {
 
     #[test]
     fn invalid_input_returns_err() {
-        let inputs: &[&[u8]] = &[b"gibberish", b" . ", OP_READ, OP_WRITE, OP_SEEK];
+        let inputs: &[&[u8]] = &[
+            b"gibberish",
+            b" . ",
+            OP_READ,
+            OP_WRITE,
+            OP_SEEK,
+            OP_TRUNCATE,
+        ];
 
         for input in inputs {
             let parse_result = parse_input(input);
@@ -266,12 +1019,15 @@ mod tests {
     fn invalid_number_returns_err() {
         let inputs: &[&[u8]] = &[
             b"seek x",
-            b"seek -1<",
-            b"seek +1<",
+            b"seek -<",
+            b"seek +x<",
             b"seek -+3",
             b"seek +-6",
             b"seek --2",
             b"seek ++9",
+            b"seek 0x",
+            b"seek +0x-1",
+            b"seek 0x1g",
             b"read . -1",
             b"read . x",
         ];
@@ -297,6 +1053,10 @@ mod tests {
         let from_end_1 = parse_input(b"seek 1<").unwrap();
         let from_start_0 = parse_input(b"seek 0").unwrap();
         let from_start_1 = parse_input(b"seek 1").unwrap();
+        let hex_forwards = parse_input(b"seek +0x10").unwrap();
+        let hex_backwards = parse_input(b"seek -0x10").unwrap();
+        let hex_from_end = parse_input(b"seek 0x10<").unwrap();
+        let hex_from_start = parse_input(b"seek 0X1f").unwrap();
 
         assert_eq!(dot, Seek(SeekCommand(SeekFrom::Current(0))));
         assert_eq!(forwards, Seek(SeekCommand(SeekFrom::Current(0))));
@@ -306,6 +1066,10 @@ mod tests {
         assert_eq!(from_end_1, Seek(SeekCommand(SeekFrom::End(-1))));
         assert_eq!(from_start_0, Seek(SeekCommand(SeekFrom::Start(0))));
         assert_eq!(from_start_1, Seek(SeekCommand(SeekFrom::Start(1))));
+        assert_eq!(hex_forwards, Seek(SeekCommand(SeekFrom::Current(16))));
+        assert_eq!(hex_backwards, Seek(SeekCommand(SeekFrom::Current(-16))));
+        assert_eq!(hex_from_end, Seek(SeekCommand(SeekFrom::End(-16))));
+        assert_eq!(hex_from_start, Seek(SeekCommand(SeekFrom::Start(31))));
     }
 
     #[test]
@@ -337,4 +1101,549 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn range_arg_returns_correct_range() {
+        let inputs: &[(&[u8], Range)] = &[
+            (
+                b"fillb . 00",
+                Range {
+                    seek: SeekFrom::Current(0),
+                    count: None,
+                },
+            ),
+            (
+                b"fillb 4:16 00",
+                Range {
+                    seek: SeekFrom::Start(4),
+                    count: Some(16),
+                },
+            ),
+            (
+                b"fillb 8<:0 00",
+                Range {
+                    seek: SeekFrom::End(-8),
+                    count: Some(0),
+                },
+            ),
+        ];
+
+        for (input, range) in inputs {
+            let Fillb(cmd) = parse_input(input).unwrap() else {
+                panic!("'{}' is not Fillb", String::from_utf8_lossy(input));
+            };
+            assert_eq!(&cmd.range, range);
+        }
+    }
+
+    #[test]
+    fn fill_returns_correct_pattern() {
+        let text = parse_input(b"fill .:8  ab c ").unwrap();
+        let hex = parse_input(b"fillb .:8 de AD be ef").unwrap();
+
+        assert_eq!(
+            text,
+            Fill(FillCommand {
+                range: Range {
+                    seek: SeekFrom::Current(0),
+                    count: Some(8)
+                },
+                pattern: b"ab c ".to_vec()
+            })
+        );
+        assert_eq!(
+            hex,
+            Fillb(FillbCommand {
+                range: Range {
+                    seek: SeekFrom::Current(0),
+                    count: Some(8)
+                },
+                pattern: vec![0xde, 0xad, 0xbe, 0xef]
+            })
+        );
+    }
+
+    #[test]
+    fn fill_returns_err_for_invalid_args() {
+        let inputs: &[&[u8]] = &[
+            b"fill",
+            b"fill .",
+            b"fill . \t ",
+            b"fillb .",
+            b"fillb .:x 00",
+            b"fillb .:-1 00",
+            b"fillb x:1 00",
+        ];
+
+        for input in inputs {
+            let parse_result = parse_input(input);
+            assert!(
+                parse_result.is_err(),
+                "'{}' is not Err but {:?}",
+                String::from_utf8_lossy(input),
+                parse_result
+            );
+        }
+    }
+
+    #[test]
+    fn insert_and_delete_return_correct_args() {
+        let insert = parse_input(b"insert 4  ab ").unwrap();
+        let insertb = parse_input(b"insertb < 0a 0D").unwrap();
+        let delete = parse_input(b"delete -2:2").unwrap();
+
+        assert_eq!(
+            insert,
+            Insert(InsertCommand {
+                seek: SeekFrom::Start(4),
+                index: 10
+            })
+        );
+        assert_eq!(
+            insertb,
+            Insertb(InsertbCommand {
+                seek: SeekFrom::End(0),
+                bytes: vec![0x0a, 0x0d]
+            })
+        );
+        assert_eq!(
+            delete,
+            Delete(DeleteCommand(Range {
+                seek: SeekFrom::Current(-2),
+                count: Some(2)
+            }))
+        );
+        assert!(parse_input(b"insertb 0").is_err());
+    }
+
+    #[test]
+    fn truncate_and_resize_return_same_cmd() {
+        let truncate = parse_input(b"truncate 16<").unwrap();
+        let resize = parse_input(b"resize 16<").unwrap();
+        let shrink = parse_input(b"truncate -16<").unwrap();
+        let extend = parse_input(b"resize +0x10<").unwrap();
+
+        assert_eq!(truncate, Truncate(TruncateCommand(SeekFrom::End(-16))));
+        assert_eq!(truncate, resize);
+        assert_eq!(shrink, truncate);
+        assert_eq!(extend, Truncate(TruncateCommand(SeekFrom::End(16))));
+        assert!(parse_input(b"truncate -<").is_err());
+    }
+
+    #[test]
+    fn readi_returns_err_for_invalid_type() {
+        let inputs: &[&[u8]] = &[b"readi .", b"readi . u32", b"readi . u12le"];
+
+        for input in inputs {
+            let parse_result = parse_input(input);
+            assert!(
+                parse_result.is_err(),
+                "'{}' is not Err but {:?}",
+                String::from_utf8_lossy(input),
+                parse_result
+            );
+        }
+    }
+
+    #[test]
+    fn writei_returns_correct_byte_vector() {
+        let inputs: &[(&[u8], Command)] = &[
+            (
+                b"writei 0x10 u32le 0xdeadbeef",
+                Writei(WriteiCommand {
+                    seek: SeekFrom::Start(16),
+                    bytes: vec![0xef, 0xbe, 0xad, 0xde],
+                }),
+            ),
+            (
+                b"writei . f32be -1.5",
+                Writei(WriteiCommand {
+                    seek: SeekFrom::Current(0),
+                    bytes: vec![0xbf, 0xc0, 0x00, 0x00],
+                }),
+            ),
+            (
+                b"wi . i16le -2",
+                Writei(WriteiCommand {
+                    seek: SeekFrom::Current(0),
+                    bytes: vec![0xfe, 0xff],
+                }),
+            ),
+        ];
+
+        for (input, command) in inputs {
+            assert_eq!(&parse_input(input).unwrap(), command);
+        }
+    }
+
+    #[test]
+    fn writei_returns_err_for_invalid_value() {
+        let inputs: &[&[u8]] = &[b"writei . u8", b"writei . u8 256", b"writei . i8 128"];
+
+        for input in inputs {
+            let parse_result = parse_input(input);
+            assert!(
+                parse_result.is_err(),
+                "'{}' is not Err but {:?}",
+                String::from_utf8_lossy(input),
+                parse_result
+            );
+        }
+    }
+
+    #[test]
+    fn template_commands_return_correct_args() {
+        let load = parse_input(b"template load  my structs.th ").unwrap();
+        let list = parse_input(b"template list").unwrap();
+        let decode = parse_input(b"decode 0x10 header").unwrap();
+        let decode_array = parse_input(b"decode-array . entry 4").unwrap();
+
+        assert_eq!(
+            load,
+            Template(TemplateCommand::Load("my structs.th".into()))
+        );
+        assert_eq!(list, Template(TemplateCommand::List));
+        assert_eq!(
+            decode,
+            Decode(DecodeCommand {
+                seek: SeekFrom::Start(16),
+                name: "header".into()
+            })
+        );
+        assert_eq!(
+            decode_array,
+            DecodeArray(DecodeArrayCommand {
+                seek: SeekFrom::Current(0),
+                name: "entry".into(),
+                count: 4
+            })
+        );
+    }
+
+    #[test]
+    fn template_commands_return_err_for_missing_args() {
+        let inputs: &[&[u8]] = &[
+            b"template",
+            b"template load",
+            b"template x",
+            b"decode .",
+            b"decode-array . entry",
+        ];
+
+        for input in inputs {
+            let parse_result = parse_input(input);
+            assert!(
+                parse_result.is_err(),
+                "'{}' is not Err but {:?}",
+                String::from_utf8_lossy(input),
+                parse_result
+            );
+        }
+    }
+
+    #[test]
+    fn set_returns_correct_args() {
+        let set = parse_input(b"set 0x10 file.recs[1].name  a b").unwrap();
+
+        assert_eq!(
+            set,
+            Set(SetCommand {
+                seek: SeekFrom::Start(16),
+                name: "file".into(),
+                field: "recs[1].name".into(),
+                value: b"a b".to_vec()
+            })
+        );
+        // Kept for text fields, and ignored when encoding numbers.
+        assert_eq!(
+            parse_input(b"set 0 file.id 2 ").unwrap(),
+            Set(SetCommand {
+                seek: SeekFrom::Start(0),
+                name: "file".into(),
+                field: "id".into(),
+                value: b"2 ".to_vec()
+            })
+        );
+        assert!(parse_input(b"set . file 1").is_err());
+        assert!(parse_input(b"set . file. 1").is_err());
+        assert!(parse_input(b"set . file.id").is_err());
+    }
+
+    #[test]
+    fn varint_commands_return_correct_args() {
+        let readv = parse_input(b"readv . zigzag").unwrap();
+        let writev = parse_input(b"wv 4 sleb128 -123456").unwrap();
+
+        assert_eq!(
+            readv,
+            Readv(ReadvCommand {
+                seek: SeekFrom::Current(0),
+                format: varint::Format::Zigzag
+            })
+        );
+        assert_eq!(
+            writev,
+            Writev(WritevCommand {
+                seek: SeekFrom::Start(4),
+                bytes: vec![0xc0, 0xbb, 0x78]
+            })
+        );
+        assert!(parse_input(b"readv . leb").is_err());
+        assert!(parse_input(b"writev . uleb128 -1").is_err());
+    }
+
+    #[test]
+    fn bit_commands_return_correct_args() {
+        let readbits = parse_input(b"readbits 2 4 LSB").unwrap();
+        let setbits = parse_input(b"setbits . 80 01").unwrap();
+        let togglebits = parse_input(b"togglebits 1< ff").unwrap();
+
+        assert_eq!(
+            readbits,
+            Readbits(ReadbitsCommand {
+                seek: SeekFrom::Start(2),
+                count: 4,
+                order: BitOrder::LsbFirst
+            })
+        );
+        assert_eq!(
+            setbits,
+            Setbits(BitsCommand {
+                seek: SeekFrom::Current(0),
+                mask: vec![0x80, 0x01]
+            })
+        );
+        assert_eq!(
+            togglebits,
+            Togglebits(BitsCommand {
+                seek: SeekFrom::End(-1),
+                mask: vec![0xff]
+            })
+        );
+        assert!(parse_input(b"readbits . 1 mid").is_err());
+        assert!(parse_input(b"clearbits .").is_err());
+    }
+
+    #[test]
+    fn transform_returns_correct_args() {
+        let xor = parse_input(b"transform 0x10:8 XOR 5a a5").unwrap();
+        let not = parse_input(b"transform . not").unwrap();
+
+        assert_eq!(
+            xor,
+            Transform(TransformCommand {
+                range: Range {
+                    seek: SeekFrom::Start(16),
+                    count: Some(8)
+                },
+                op: TransformOp::Xor,
+                key: vec![0x5a, 0xa5]
+            })
+        );
+        assert_eq!(
+            not,
+            Transform(TransformCommand {
+                range: Range {
+                    seek: SeekFrom::Current(0),
+                    count: None
+                },
+                op: TransformOp::Not,
+                key: vec![]
+            })
+        );
+
+        let inputs: &[&[u8]] = &[
+            b"transform .",
+            b"transform . nand ff",
+            b"transform . xor",
+            b"transform . not ff",
+        ];
+        for input in inputs {
+            let parse_result = parse_input(input);
+            assert!(
+                parse_result.is_err(),
+                "'{}' is not Err but {:?}",
+                String::from_utf8_lossy(input),
+                parse_result
+            );
+        }
+    }
+
+    #[test]
+    fn swap_returns_err_for_invalid_width() {
+        let inputs: &[&[u8]] = &[b"swap 0:8", b"swap 0:8 3", b"swap 0:8 16", b"reverse"];
+
+        for input in inputs {
+            let parse_result = parse_input(input);
+            assert!(
+                parse_result.is_err(),
+                "'{}' is not Err but {:?}",
+                String::from_utf8_lossy(input),
+                parse_result
+            );
+        }
+    }
+
+    #[test]
+    fn dump_returns_correct_args() {
+        let cmd = parse_input(b"dump 0x10:8 Rust").unwrap();
+
+        assert_eq!(
+            cmd,
+            Dump(DumpCommand {
+                range: Range {
+                    seek: SeekFrom::Start(16),
+                    count: Some(8)
+                },
+                format: export::Format::Rust
+            })
+        );
+        assert!(parse_input(b"dump 0 xml").is_err());
+    }
+
+    #[test]
+    fn writeenc_decodes_data() {
+        let cmd = parse_input(b"writeenc 4 base64 aGk=").unwrap();
+        let file_cmd = parse_input(b"writeenc-file . xxd my dump.txt ").unwrap();
+
+        assert_eq!(
+            cmd,
+            Writeenc(WriteencCommand {
+                seek: SeekFrom::Start(4),
+                bytes: b"hi".to_vec()
+            })
+        );
+        assert_eq!(
+            file_cmd,
+            WriteencFile(WriteencFileCommand {
+                seek: SeekFrom::Current(0),
+                encoding: Encoding::Xxd,
+                path: "my dump.txt".to_string()
+            })
+        );
+        assert!(parse_input(b"writeenc 0 hex abc").is_err());
+        assert!(parse_input(b"writeenc-file 0 hex").is_err());
+    }
+
+    #[test]
+    fn load_returns_correct_args() {
+        let ihex = parse_input(b"load-ihex fw.hex").unwrap();
+        let srec = parse_input(b"load-srec fw.s19 -0x100").unwrap();
+
+        assert_eq!(
+            ihex,
+            Load(LoadCommand {
+                format: records::Format::Ihex,
+                path: "fw.hex".to_string(),
+                base: 0
+            })
+        );
+        assert_eq!(
+            srec,
+            Load(LoadCommand {
+                format: records::Format::Srec,
+                path: "fw.s19".to_string(),
+                base: -0x100
+            })
+        );
+        assert!(parse_input(b"load-ihex").is_err());
+        assert!(parse_input(b"load-ihex   ").is_err());
+
+        let paths: &[(&[u8], &str, i64)] = &[
+            (b"load-ihex my fw.hex", "my fw.hex", 0),
+            (b"load-ihex  my fw.hex  0x10 ", "my fw.hex", 0x10),
+            (b"load-ihex fw.hex 0xzz", "fw.hex 0xzz", 0),
+            (b"load-ihex 100", "100", 0),
+        ];
+        for (input, path, base) in paths {
+            assert_eq!(
+                parse_input(input).unwrap(),
+                Load(LoadCommand {
+                    format: records::Format::Ihex,
+                    path: path.to_string(),
+                    base: *base
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn raw_reads_are_marked() {
+        let read = parse_input(b"r 0 4").unwrap();
+        let raw_read = parse_input(b"READ! 0 4").unwrap();
+
+        assert_eq!(
+            read,
+            Read(ReadCommand {
+                seek: SeekFrom::Start(0),
+                count: Some(4),
+                raw: false
+            })
+        );
+        assert_eq!(
+            raw_read,
+            Read(ReadCommand {
+                seek: SeekFrom::Start(0),
+                count: Some(4),
+                raw: true
+            })
+        );
+    }
+
+    #[test]
+    fn string_reads_return_correct_args() {
+        let reads = parse_input(b"reads . 64").unwrap();
+        let readp = parse_input(b"readp 4 u16be").unwrap();
+
+        assert_eq!(
+            reads,
+            Reads(ReadsCommand {
+                seek: SeekFrom::Current(0),
+                max: Some(64)
+            })
+        );
+        assert_eq!(
+            readp,
+            Readp(ReadpCommand {
+                seek: SeekFrom::Start(4),
+                prefix: ValueType::parse(b"u16be").unwrap()
+            })
+        );
+        assert!(parse_input(b"readp . i8").is_err());
+        assert!(parse_input(b"readp . f32le").is_err());
+    }
+
+    #[test]
+    fn set_dispatches_on_first_arg() {
+        let list = parse_input(b"set").unwrap();
+        let setting = parse_input(b"set hex.group 4").unwrap();
+        let field = parse_input(b"set 0 header.version 2").unwrap();
+
+        assert_eq!(list, Config(None));
+        assert_eq!(
+            setting,
+            Config(Some(Setting::Hexdump(LayoutOption::Group(4))))
+        );
+        assert!(matches!(field, Set(_)));
+        assert!(parse_input(b"set hex.width").is_err());
+    }
+
+    #[test]
+    fn readb_returns_layout_options() {
+        let readb = parse_input(b"readb 0 width=8 32 group=1").unwrap();
+
+        assert_eq!(
+            readb,
+            Readb(ReadbCommand {
+                seek: SeekFrom::Start(0),
+                count: Some(32),
+                options: vec![LayoutOption::Width(8), LayoutOption::Group(1)]
+            })
+        );
+        assert!(parse_input(b"readb 0 group=3").is_err());
+        assert_eq!(
+            parse_input(b"readb 0 16 32").unwrap_err().to_string(),
+            strings::UNEXPECTED_ARG
+        );
+    }
 }
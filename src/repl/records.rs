@@ -0,0 +1,300 @@
+//! Intel HEX and Motorola S-record files, which hold data records tagged
+//! with the address they belong at.
+
+use std::fmt::Write;
+
+use crate::{repl::encoding::decode_hex, strings};
+
+/// Data bytes per record when writing.
+const RECORD_LEN: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Ihex,
+    Srec,
+}
+
+impl Format {
+    pub fn parse(&self, text: &[u8]) -> Result<Vec<Chunk>, String> {
+        match self {
+            Format::Ihex => parse_ihex(text),
+            Format::Srec => parse_srec(text),
+        }
+    }
+}
+
+/// Data from a data record, and the address it belongs at.
+#[derive(Debug, PartialEq)]
+pub struct Chunk {
+    pub address: u64,
+    pub data: Vec<u8>,
+}
+
+/// Formats `bytes` belonging at `address` as Intel HEX records, with
+/// extended linear address records for addresses past 64 KiB.
+pub fn format_ihex(address: u64, bytes: &[u8]) -> Result<String, &'static str> {
+    if address + bytes.len() as u64 > 1 << 32 {
+        return Err(strings::ADDRESS_OUT_OF_RANGE);
+    }
+
+    let mut output = String::new();
+    let mut upper = 0;
+    let mut offset = 0;
+
+    while offset < bytes.len() {
+        let address = address + offset as u64;
+        if address >> 16 != upper {
+            upper = address >> 16;
+            write_ihex_record(&mut output, 0, 0x04, &(upper as u16).to_be_bytes());
+        }
+
+        // Records can't cross a 64 KiB boundary.
+        let len = RECORD_LEN
+            .min(bytes.len() - offset)
+            .min(0x10000 - (address & 0xffff) as usize);
+        write_ihex_record(
+            &mut output,
+            address as u16,
+            0x00,
+            &bytes[offset..offset + len],
+        );
+        offset += len;
+    }
+    write_ihex_record(&mut output, 0, 0x01, &[]);
+
+    Ok(output)
+}
+
+fn write_ihex_record(output: &mut String, address: u16, kind: u8, data: &[u8]) {
+    let [address_high, address_low] = address.to_be_bytes();
+    let mut record = vec![data.len() as u8, address_high, address_low, kind];
+    record.extend_from_slice(data);
+    record.push(checksum(&record).wrapping_neg());
+
+    output.push(':');
+    write_hex(output, &record);
+    output.push('\n');
+}
+
+/// Formats `bytes` belonging at `address` as Motorola S-records. The
+/// narrowest address size that fits is used.
+pub fn format_srec(address: u64, bytes: &[u8]) -> Result<String, &'static str> {
+    let end = address + bytes.len() as u64;
+    let address_len = match end {
+        0..=0x10000 => 2,
+        0x10001..=0x1000000 => 3,
+        0x1000001..=0x100000000 => 4,
+        _ => return Err(strings::ADDRESS_OUT_OF_RANGE),
+    };
+    // Data and termination record types for each address size.
+    let (data_kind, end_kind) = match address_len {
+        2 => (1, 9),
+        3 => (2, 8),
+        _ => (3, 7),
+    };
+
+    let mut output = String::new();
+    write_srec_record(&mut output, 0, 0, 2, &[]);
+
+    let mut count = 0;
+    for (index, chunk) in bytes.chunks(RECORD_LEN).enumerate() {
+        let address = address + (index * RECORD_LEN) as u64;
+        write_srec_record(&mut output, data_kind, address, address_len, chunk);
+        count += 1;
+    }
+    if count <= 0xffff {
+        write_srec_record(&mut output, 5, count, 2, &[]);
+    } else {
+        write_srec_record(&mut output, 6, count, 3, &[]);
+    }
+    write_srec_record(&mut output, end_kind, 0, address_len, &[]);
+
+    Ok(output)
+}
+
+fn write_srec_record(output: &mut String, kind: u8, address: u64, address_len: usize, data: &[u8]) {
+    let mut record = vec![(address_len + data.len() + 1) as u8];
+    record.extend_from_slice(&address.to_be_bytes()[8 - address_len..]);
+    record.extend_from_slice(data);
+    record.push(!checksum(&record));
+
+    write!(output, "S{kind}").unwrap();
+    write_hex(output, &record);
+    output.push('\n');
+}
+
+/// Parses the data records of an Intel HEX file, applying extended segment
+/// and linear addresses. Returns an error naming the offending line.
+pub fn parse_ihex(text: &[u8]) -> Result<Vec<Chunk>, String> {
+    let mut chunks = Vec::new();
+    let mut base = 0;
+
+    for (i, line) in text.split(|c| *c == b'\n').enumerate() {
+        let line_error = |e: &str| format!("line {}: {e}", i + 1);
+
+        let line = line.trim_ascii();
+        if line.is_empty() {
+            continue;
+        }
+
+        let record = line
+            .strip_prefix(b":")
+            .and_then(decode_hex)
+            .ok_or(line_error(strings::INVALID_RECORD))?;
+        let [len, address_high, address_low, kind, ref rest @ ..] = record[..] else {
+            return Err(line_error(strings::INVALID_RECORD));
+        };
+        if rest.len() != len as usize + 1 {
+            return Err(line_error(strings::INVALID_RECORD));
+        }
+        if checksum(&record) != 0 {
+            return Err(line_error(strings::RECORD_CHECKSUM_MISMATCH));
+        }
+
+        let data = &rest[..len as usize];
+        let address = u16::from_be_bytes([address_high, address_low]) as u64;
+
+        match (kind, data) {
+            (0x00, _) => chunks.push(Chunk {
+                address: base + address,
+                data: data.to_vec(),
+            }),
+            (0x01, _) => break,
+            (0x02, [high, low]) => base = (u16::from_be_bytes([*high, *low]) as u64) << 4,
+            (0x04, [high, low]) => base = (u16::from_be_bytes([*high, *low]) as u64) << 16,
+            // Start addresses don't place any data.
+            (0x03 | 0x05, _) => {}
+            (0x02 | 0x04, _) => return Err(line_error(strings::INVALID_RECORD)),
+            _ => return Err(line_error(strings::UNKNOWN_RECORD_TYPE)),
+        }
+    }
+
+    Ok(chunks)
+}
+
+/// Parses the data records of a Motorola S-record file. Returns an error
+/// naming the offending line.
+pub fn parse_srec(text: &[u8]) -> Result<Vec<Chunk>, String> {
+    let mut chunks = Vec::new();
+
+    for (i, line) in text.split(|c| *c == b'\n').enumerate() {
+        let line_error = |e: &str| format!("line {}: {e}", i + 1);
+
+        let line = line.trim_ascii();
+        if line.is_empty() {
+            continue;
+        }
+
+        let [b'S' | b's', kind, ref digits @ ..] = line[..] else {
+            return Err(line_error(strings::INVALID_RECORD));
+        };
+        let address_len = match kind {
+            b'0' | b'1' | b'5' | b'9' => 2,
+            b'2' | b'6' | b'8' => 3,
+            b'3' | b'7' => 4,
+            _ => return Err(line_error(strings::UNKNOWN_RECORD_TYPE)),
+        };
+
+        let record = decode_hex(digits).ok_or(line_error(strings::INVALID_RECORD))?;
+        let [count, ref rest @ ..] = record[..] else {
+            return Err(line_error(strings::INVALID_RECORD));
+        };
+        if rest.len() != count as usize || rest.len() < address_len + 1 {
+            return Err(line_error(strings::INVALID_RECORD));
+        }
+        if checksum(&record) != 0xff {
+            return Err(line_error(strings::RECORD_CHECKSUM_MISMATCH));
+        }
+
+        let (address, data) = rest[..rest.len() - 1].split_at(address_len);
+        let address = address.iter().fold(0, |acc, byte| acc << 8 | *byte as u64);
+
+        match kind {
+            b'1'..=b'3' => chunks.push(Chunk {
+                address,
+                data: data.to_vec(),
+            }),
+            b'7'..=b'9' => break,
+            // Headers and record counts.
+            _ => {}
+        }
+    }
+
+    Ok(chunks)
+}
+
+/// Returns the low byte of the sum of `bytes`. Intel HEX records end with
+/// its negation, and S-records with its complement.
+fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |sum, byte| sum.wrapping_add(*byte))
+}
+
+fn write_hex(output: &mut String, bytes: &[u8]) {
+    for byte in bytes {
+        write!(output, "{byte:02X}").unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ihex_records_are_formatted_correctly() {
+        let output = format_ihex(0xfffe, b"\x01\x02\x03").unwrap();
+
+        assert_eq!(
+            output,
+            ":02FFFE000102FE\n:020000040001F9\n:0100000003FC\n:00000001FF\n"
+        );
+        assert!(format_ihex(0xffff_ffff, b"\x01\x02").is_err());
+    }
+
+    #[test]
+    fn formatted_records_are_parsed_back() {
+        let bytes: Vec<u8> = (0..=255).collect();
+
+        let ihex = format_ihex(0x1_fff0, &bytes).unwrap();
+        let srec = format_srec(0x1_fff0, &bytes).unwrap();
+
+        for chunks in [parse_ihex(ihex.as_bytes()), parse_srec(srec.as_bytes())] {
+            let chunks = chunks.unwrap();
+            assert_eq!(chunks[0].address, 0x1_fff0);
+            assert_eq!(chunks[1].address, 0x2_0000);
+            let data: Vec<u8> = chunks.into_iter().flat_map(|chunk| chunk.data).collect();
+            assert_eq!(data, bytes);
+        }
+    }
+
+    #[test]
+    fn invalid_records_return_err() {
+        let ihex: &[&[u8]] = &[
+            b":0100000041BF",
+            b":0200000041BE",
+            b":0100000641B8",
+            b"0100000041BE",
+        ];
+        let srec: &[&[u8]] = &[b"S1051000414A", b"S4041000414A", b"S10410004149"];
+
+        for text in ihex {
+            assert!(parse_ihex(text).is_err());
+        }
+        for text in srec {
+            assert!(parse_srec(text).is_err());
+        }
+        assert_eq!(
+            parse_ihex(b":0100000041BE\n:0100000041BF"),
+            Err("line 2: Record checksum mismatch.".to_string())
+        );
+    }
+
+    #[test]
+    fn srec_records_are_formatted_correctly() {
+        let output = format_srec(0x1000, b"ABC").unwrap();
+
+        assert_eq!(
+            output,
+            "S0030000FC\nS106100041424323\nS5030001FB\nS9030000FC\n"
+        );
+    }
+}
@@ -0,0 +1,110 @@
+//! Settings for the session, which can be changed with the `set` command.
+
+use crate::{
+    repl::hexdump::{Layout, LayoutOption},
+    strings,
+};
+
+#[derive(Debug, Default)]
+pub struct Settings {
+    /// Show the file size in the prompt.
+    pub show_size: bool,
+    /// Escape unprintable bytes printed by `read`.
+    pub escape_reads: bool,
+    /// Layout of `readb` hex dumps.
+    pub hexdump: Layout,
+}
+
+/// A change to one setting.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Setting {
+    ShowSize(bool),
+    EscapeReads(bool),
+    Hexdump(LayoutOption),
+}
+
+const HEXDUMP_PREFIX: &[u8] = b"hex.";
+
+impl Setting {
+    /// Parses a setting from its name, e.g. `hex.width`, and value.
+    pub fn parse(name: &[u8], value: &[u8]) -> Result<Setting, &'static str> {
+        let name = name.to_ascii_lowercase();
+        let value = value.to_ascii_lowercase();
+
+        if let Some(name) = name.strip_prefix(HEXDUMP_PREFIX) {
+            return LayoutOption::parse(name, &value).map(Setting::Hexdump);
+        }
+
+        match name.as_slice() {
+            b"prompt.size" => parse_switch(&value).map(Setting::ShowSize),
+            b"read.escape" => parse_switch(&value).map(Setting::EscapeReads),
+            _ => Err(strings::UNKNOWN_SETTING),
+        }
+    }
+}
+
+impl Settings {
+    pub fn apply(&mut self, setting: Setting) {
+        match setting {
+            Setting::ShowSize(show_size) => self.show_size = show_size,
+            Setting::EscapeReads(escape_reads) => self.escape_reads = escape_reads,
+            Setting::Hexdump(option) => self.hexdump.apply(option),
+        }
+    }
+
+    /// Returns the name and value of each setting, for listing.
+    pub fn entries(&self) -> Vec<(String, String)> {
+        let mut entries = vec![
+            ("prompt.size".to_string(), format_switch(self.show_size)),
+            ("read.escape".to_string(), format_switch(self.escape_reads)),
+        ];
+
+        let prefix = String::from_utf8_lossy(HEXDUMP_PREFIX);
+        entries.extend(
+            self.hexdump
+                .entries()
+                .into_iter()
+                .map(|(name, value)| (format!("{prefix}{name}"), value)),
+        );
+
+        entries
+    }
+}
+
+pub fn parse_switch(value: &[u8]) -> Result<bool, &'static str> {
+    match value {
+        b"on" => Ok(true),
+        b"off" => Ok(false),
+        _ => Err(strings::INVALID_SWITCH),
+    }
+}
+
+pub fn format_switch(value: bool) -> String {
+    if value { "on" } else { "off" }.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repl::hexdump::OffsetRadix;
+
+    #[test]
+    fn settings_are_applied_correctly() {
+        let mut settings = Settings::default();
+
+        settings.apply(Setting::parse(b"prompt.size", b"on").unwrap());
+        settings.apply(Setting::parse(b"HEX.OFFSET", b"HEX").unwrap());
+        settings.apply(Setting::parse(b"hex.width", b"0x20").unwrap());
+
+        assert!(settings.show_size);
+        assert_eq!(settings.hexdump.offset_radix, OffsetRadix::Hex);
+        assert_eq!(settings.hexdump.width, 32);
+    }
+
+    #[test]
+    fn unknown_setting_returns_err() {
+        assert!(Setting::parse(b"width", b"16").is_err());
+        assert!(Setting::parse(b"hex.", b"16").is_err());
+        assert!(Setting::parse(b"prompt.size", b"yes").is_err());
+    }
+}
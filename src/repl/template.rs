@@ -0,0 +1,620 @@
+//! Structure templates for decoding records.
+//!
+//! A template file declares structures like this:
+//!
+//! ```text
+//! # Comments start with `#`.
+//! struct header {
+//!     magic: char[4]
+//!     version: u16le
+//!     _: pad[2]
+//!     sizes: u32le[4]
+//! }
+//!
+//! struct entry {
+//!     head: header
+//!     name: char[16]
+//! }
+//! ```
+//!
+//! A field's type is a numeric type such as `u32le`, `char` for text, `pad`
+//! for bytes to skip, or the name of a structure declared before it. Any type
+//! can be followed by `[count]` to make an array. Arrays of `char` are decoded
+//! as a single NUL-terminated string.
+
+use std::fmt::{self, Write};
+
+use crate::{
+    repl::{
+        display,
+        value::{ValueType, parse_integer},
+    },
+    strings,
+};
+
+#[derive(Debug, Default)]
+pub struct Templates {
+    /// Structures in the order they were loaded. A redefined structure hides
+    /// the old one, but structures declared before then keep using the old one.
+    structs: Vec<Struct>,
+}
+
+#[derive(Debug)]
+pub struct Struct {
+    pub name: String,
+    /// Size in bytes.
+    pub size: usize,
+    fields: Vec<Field>,
+}
+
+#[derive(Debug)]
+struct Field {
+    name: String,
+    kind: FieldKind,
+    count: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum FieldKind {
+    Value(ValueType),
+    Char,
+    Pad,
+    /// Index into `Templates::structs`.
+    Struct(usize),
+}
+
+/// A field that holds a value, with nested structure and array fields spelled
+/// out, e.g. `head.sizes[2]`.
+#[derive(Debug, PartialEq)]
+pub struct FlatField {
+    pub name: String,
+    /// Offset from the start of the outermost structure.
+    pub offset: usize,
+    pub kind: FlatKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlatKind {
+    Value(ValueType),
+    /// A string of the given length.
+    Text(usize),
+}
+
+impl FlatKind {
+    pub fn len(&self) -> usize {
+        match self {
+            FlatKind::Value(value_type) => value_type.width,
+            FlatKind::Text(len) => *len,
+        }
+    }
+
+    /// Formats `bytes` as a value of this kind. Numbers are followed by their
+    /// bits in hex if `with_hex` is set. `bytes` must be `self.len()` long.
+    pub fn format(&self, bytes: &[u8], with_hex: bool) -> String {
+        match self {
+            FlatKind::Value(value_type) if with_hex => value_type.format(bytes),
+            FlatKind::Value(value_type) => value_type.decimal(bytes),
+            FlatKind::Text(_) => {
+                let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+                display::quote(&bytes[..end])
+            }
+        }
+    }
+
+    /// Encodes `text` as a value of this kind. Whitespace around numbers is
+    /// ignored, and strings shorter than the field are padded with NULs.
+    pub fn encode(&self, text: &[u8]) -> Result<Vec<u8>, &'static str> {
+        match self {
+            FlatKind::Value(value_type) => value_type.encode(text.trim_ascii()),
+            FlatKind::Text(len) => {
+                if text.len() > *len {
+                    return Err(strings::TEXT_TOO_LONG);
+                }
+                let mut bytes = text.to_vec();
+                bytes.resize(*len, 0);
+                Ok(bytes)
+            }
+        }
+    }
+}
+
+impl fmt::Display for FlatKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FlatKind::Value(value_type) => write!(f, "{value_type}"),
+            FlatKind::Text(len) => write!(f, "char[{len}]"),
+        }
+    }
+}
+
+impl Templates {
+    /// Parses the template file contents in `text` and adds its structures.
+    /// Returns their names, or an error naming the offending line. Nothing is
+    /// added if there is an error.
+    pub fn load(&mut self, text: &str) -> Result<Vec<String>, String> {
+        let old_len = self.structs.len();
+
+        let result = self.parse(text);
+        if result.is_err() {
+            self.structs.truncate(old_len);
+        }
+
+        result?;
+        Ok(self.structs[old_len..]
+            .iter()
+            .map(|st| st.name.clone())
+            .collect())
+    }
+
+    /// Returns the structure named `name`.
+    pub fn get(&self, name: &str) -> Option<&Struct> {
+        self.position(name).map(|i| &self.structs[i])
+    }
+
+    /// Returns the structures that can be referred to by name.
+    pub fn visible(&self) -> impl Iterator<Item = &Struct> {
+        self.structs
+            .iter()
+            .enumerate()
+            .filter(|(i, st)| self.position(&st.name) == Some(*i))
+            .map(|(_, st)| st)
+    }
+
+    /// Returns the field of `st` named `name`, e.g. `head.sizes[2]`.
+    /// The offset is worked out from the declarations, so large arrays are
+    /// indexed rather than spelled out.
+    pub fn field(&self, st: &Struct, name: &str) -> Option<FlatField> {
+        let mut st = st;
+        let mut offset = 0;
+        let mut parts = name.split('.').peekable();
+
+        while let Some(part) = parts.next() {
+            let (field_name, index) = match part.strip_suffix(']') {
+                Some(part) => {
+                    let (field_name, index) = part.split_once('[')?;
+                    let index = index
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|i| i.to_string() == index)?;
+                    (field_name, Some(index))
+                }
+                None => (part, None),
+            };
+
+            let position = st.fields.iter().position(|field| {
+                field.name == field_name && !matches!(field.kind, FieldKind::Pad)
+            })?;
+            let field = &st.fields[position];
+            let size = self.kind_size(field.kind);
+
+            offset += st.fields[..position]
+                .iter()
+                .map(|field| self.kind_size(field.kind) * field.count.unwrap_or(1))
+                .sum::<usize>();
+
+            let kind = match (field.kind, field.count, index) {
+                (FieldKind::Char, count, None) => FlatKind::Text(count.unwrap_or(1)),
+                (FieldKind::Value(value_type), None, None) => FlatKind::Value(value_type),
+                (FieldKind::Value(value_type), Some(count), Some(i)) if i < count => {
+                    offset += i * size;
+                    FlatKind::Value(value_type)
+                }
+                (FieldKind::Struct(j), None, None) => {
+                    st = &self.structs[j];
+                    parts.peek()?;
+                    continue;
+                }
+                (FieldKind::Struct(j), Some(count), Some(i)) if i < count => {
+                    offset += i * size;
+                    st = &self.structs[j];
+                    parts.peek()?;
+                    continue;
+                }
+                _ => return None,
+            };
+
+            return parts.next().is_none().then(|| FlatField {
+                name: name.to_string(),
+                offset,
+                kind,
+            });
+        }
+
+        None
+    }
+
+    /// Returns the fields of `st` that hold values, in order.
+    pub fn fields(&self, st: &Struct) -> Vec<FlatField> {
+        let mut fields = Vec::new();
+        self.flatten(st, 0, "", &mut fields);
+        fields
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.structs.iter().rposition(|st| st.name == name)
+    }
+
+    fn kind_size(&self, kind: FieldKind) -> usize {
+        match kind {
+            FieldKind::Value(value_type) => value_type.width,
+            FieldKind::Char | FieldKind::Pad => 1,
+            FieldKind::Struct(i) => self.structs[i].size,
+        }
+    }
+
+    fn flatten(&self, st: &Struct, base: usize, prefix: &str, out: &mut Vec<FlatField>) {
+        let mut offset = base;
+
+        for field in &st.fields {
+            let name = format!("{prefix}{}", field.name);
+            let size = self.kind_size(field.kind);
+
+            match (field.kind, field.count) {
+                (FieldKind::Pad, _) => {}
+                (FieldKind::Char, count) => out.push(FlatField {
+                    name,
+                    offset,
+                    kind: FlatKind::Text(count.unwrap_or(1)),
+                }),
+                (FieldKind::Value(value_type), None) => out.push(FlatField {
+                    name,
+                    offset,
+                    kind: FlatKind::Value(value_type),
+                }),
+                (FieldKind::Value(value_type), Some(count)) => {
+                    for i in 0..count {
+                        out.push(FlatField {
+                            name: format!("{name}[{i}]"),
+                            offset: offset + i * size,
+                            kind: FlatKind::Value(value_type),
+                        });
+                    }
+                }
+                (FieldKind::Struct(j), None) => {
+                    self.flatten(&self.structs[j], offset, &format!("{name}."), out);
+                }
+                (FieldKind::Struct(j), Some(count)) => {
+                    for i in 0..count {
+                        let prefix = format!("{name}[{i}].");
+                        self.flatten(&self.structs[j], offset + i * size, &prefix, out);
+                    }
+                }
+            }
+
+            offset += size * field.count.unwrap_or(1);
+        }
+    }
+
+    fn parse(&mut self, text: &str) -> Result<(), String> {
+        let mut current: Option<(String, Vec<Field>)> = None;
+
+        for (i, line) in text.lines().enumerate() {
+            let line_error = |e: &str| format!("line {}: {e}", i + 1);
+
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let Some((name, fields)) = current.as_mut() else {
+                let name = line
+                    .strip_prefix("struct")
+                    .filter(|rest| rest.starts_with(char::is_whitespace))
+                    .and_then(|rest| rest.strip_suffix('{'))
+                    .map(str::trim)
+                    .ok_or(line_error(strings::TEMPLATE_EXPECTED_STRUCT))?;
+                if !is_identifier(name) {
+                    return Err(line_error(strings::TEMPLATE_INVALID_NAME));
+                }
+                current = Some((name.to_string(), Vec::new()));
+                continue;
+            };
+
+            if line == "}" {
+                let size = fields.iter().try_fold(0usize, |acc, field| {
+                    let size = self.kind_size(field.kind);
+                    size.checked_mul(field.count.unwrap_or(1))
+                        .and_then(|size| acc.checked_add(size))
+                });
+                let size = size.ok_or(line_error(strings::TEMPLATE_TOO_LARGE))?;
+
+                self.structs.push(Struct {
+                    name: std::mem::take(name),
+                    size,
+                    fields: std::mem::take(fields),
+                });
+                current = None;
+                continue;
+            }
+
+            let field = self.parse_field(line).map_err(line_error)?;
+            if field.name != "_" && fields.iter().any(|f| f.name == field.name) {
+                return Err(line_error(strings::TEMPLATE_DUPLICATE_FIELD));
+            }
+            fields.push(field);
+        }
+
+        if current.is_some() {
+            return Err(strings::TEMPLATE_UNCLOSED_STRUCT.into());
+        }
+
+        Ok(())
+    }
+
+    fn parse_field(&self, line: &str) -> Result<Field, &'static str> {
+        let line = line.trim_end_matches([';', ',']);
+        let (name, type_name) = line
+            .split_once(':')
+            .ok_or(strings::TEMPLATE_EXPECTED_FIELD)?;
+        let (name, type_name) = (name.trim(), type_name.trim());
+
+        if !is_identifier(name) {
+            return Err(strings::TEMPLATE_INVALID_NAME);
+        }
+
+        let (type_name, count) = match type_name
+            .strip_suffix(']')
+            .and_then(|type_name| type_name.split_once('['))
+        {
+            Some((type_name, count)) => {
+                let count = parse_integer(count.trim().as_bytes())
+                    .and_then(|count| usize::try_from(count).ok())
+                    .ok_or(strings::TEMPLATE_INVALID_COUNT)?;
+                (type_name.trim_end(), Some(count))
+            }
+            None => (type_name, None),
+        };
+
+        let kind = match type_name {
+            "char" => FieldKind::Char,
+            "pad" => FieldKind::Pad,
+            _ => match ValueType::parse(type_name.as_bytes()) {
+                Some(value_type) => FieldKind::Value(value_type),
+                None => FieldKind::Struct(
+                    self.position(type_name)
+                        .ok_or(strings::TEMPLATE_UNKNOWN_TYPE)?,
+                ),
+            },
+        };
+
+        Ok(Field {
+            name: name.to_string(),
+            kind,
+            count,
+        })
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Formats a structure at `pos` as a list of fields with their offsets, types
+/// and values. `bytes` must be as long as the structure.
+pub fn format_struct(fields: &[FlatField], bytes: &[u8], pos: u64) -> String {
+    let rows: Vec<[String; 4]> = fields
+        .iter()
+        .map(|field| {
+            let value = &bytes[field.offset..field.offset + field.kind.len()];
+            [
+                (pos + field.offset as u64).to_string(),
+                field.name.clone(),
+                field.kind.to_string(),
+                field.kind.format(value, true),
+            ]
+        })
+        .collect();
+
+    format_rows(["offset", "field", "type", "value"], &rows)
+}
+
+/// Formats `count` consecutive structures at `pos` as a table with a row per
+/// structure and a column per field. `bytes` must be as long as all of them.
+pub fn format_array(fields: &[FlatField], size: usize, bytes: &[u8], pos: u64) -> String {
+    let mut header = vec!["offset".to_string(), "#".to_string()];
+    header.extend(fields.iter().map(|field| field.name.clone()));
+
+    let count = bytes.len().checked_div(size).unwrap_or(0);
+    let rows: Vec<Vec<String>> = (0..count)
+        .map(|i| {
+            let record = &bytes[i * size..(i + 1) * size];
+            let mut row = vec![(pos + (i * size) as u64).to_string(), i.to_string()];
+            row.extend(fields.iter().map(|field| {
+                let value = &record[field.offset..field.offset + field.kind.len()];
+                field.kind.format(value, false)
+            }));
+            row
+        })
+        .collect();
+
+    format_rows(header, &rows)
+}
+
+/// Lays out `rows` under `header` in aligned columns. The first column is
+/// right-aligned.
+fn format_rows<H, R>(header: H, rows: &[R]) -> String
+where
+    H: IntoIterator<Item: AsRef<str>>,
+    R: AsRef<[String]>,
+{
+    let header: Vec<String> = header
+        .into_iter()
+        .map(|name| name.as_ref().to_string())
+        .collect();
+
+    let mut widths: Vec<usize> = header.iter().map(String::len).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row.as_ref()) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut output = String::new();
+    for row in std::iter::once(header.as_slice()).chain(rows.iter().map(AsRef::as_ref)) {
+        let mut line = String::new();
+        for (i, (cell, width)) in row.iter().zip(&widths).enumerate() {
+            match i {
+                0 => write!(line, "{cell:>width$}"),
+                _ => write!(line, "  {cell:<width$}"),
+            }
+            .unwrap();
+        }
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = "
+        # A header.
+        struct header {
+            magic: char[4]
+            version: u16le   # Trailing comment.
+            _: pad[2];
+            sizes: u8[2],
+        }
+
+        struct entry {
+            head: header
+            flags: u32be
+        }
+    ";
+
+    #[test]
+    fn template_is_loaded_correctly() {
+        let mut templates = Templates::default();
+
+        let names = templates.load(TEMPLATE).unwrap();
+        let entry = templates.get("entry").unwrap();
+
+        assert_eq!(names, ["header", "entry"]);
+        assert_eq!(templates.get("header").unwrap().size, 10);
+        assert_eq!(entry.size, 14);
+        assert_eq!(
+            templates
+                .fields(entry)
+                .iter()
+                .map(|field| (field.name.as_str(), field.offset))
+                .collect::<Vec<_>>(),
+            [
+                ("head.magic", 0),
+                ("head.version", 4),
+                ("head.sizes[0]", 8),
+                ("head.sizes[1]", 9),
+                ("flags", 10)
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_template_returns_err_and_loads_nothing() {
+        let inputs: &[&str] = &[
+            "struct a {",
+            "struct a {\n x: u32\n}",
+            "struct a {\n x: b\n}",
+            "struct a {\n x: u8[-1]\n}",
+            "struct a {\n x u8\n}",
+            "struct a {\n x: u8\n x: u8\n}",
+            "struct 1a {\n}",
+            "x: u8",
+            "struct a {\n}\nstruct b {\n x: c\n}",
+        ];
+
+        for input in inputs {
+            let mut templates = Templates::default();
+            assert!(templates.load(input).is_err(), "'{input}' is not Err");
+            assert!(templates.get("a").is_none());
+        }
+    }
+
+    #[test]
+    fn redefined_struct_hides_old_one() {
+        let mut templates = Templates::default();
+
+        templates.load(TEMPLATE).unwrap();
+        templates.load("struct header {\n x: u8\n}").unwrap();
+
+        assert_eq!(templates.get("header").unwrap().size, 1);
+        assert_eq!(templates.get("entry").unwrap().size, 14);
+        assert_eq!(templates.visible().count(), 2);
+    }
+
+    #[test]
+    fn struct_is_formatted_correctly() {
+        let mut templates = Templates::default();
+        templates.load(TEMPLATE).unwrap();
+        let header = templates.get("header").unwrap();
+
+        let output = format_struct(&templates.fields(header), b"ab\0\x00\x01\x00xx\x07\xff", 16);
+
+        let expected = [
+            "offset  field     type     value",
+            "    16  magic     char[4]  \"ab\"",
+            "    20  version   u16le    1 (0x0001)",
+            "    24  sizes[0]  u8       7 (0x07)",
+            "    25  sizes[1]  u8       255 (0xff)",
+        ];
+        assert_eq!(output.lines().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn field_values_are_encoded_correctly() {
+        let mut templates = Templates::default();
+        templates.load(TEMPLATE).unwrap();
+        let entry = templates.get("entry").unwrap();
+
+        let magic = templates.field(entry, "head.magic").unwrap();
+        let size = templates.field(entry, "head.sizes[1]").unwrap();
+
+        assert_eq!(magic.offset, 0);
+        assert_eq!(magic.kind.encode(b"ab").unwrap(), b"ab\0\0");
+        assert!(magic.kind.encode(b"abcde").is_err());
+        assert_eq!(size.offset, 9);
+        assert_eq!(size.kind.encode(b"0x7f").unwrap(), [0x7f]);
+        assert_eq!(size.kind.encode(b"0x7f ").unwrap(), [0x7f]);
+        assert_eq!(magic.kind.encode(b"ab ").unwrap(), b"ab \0");
+        assert!(templates.field(entry, "head.sizes[2]").is_none());
+    }
+
+    #[test]
+    fn field_offsets_are_computed_without_flattening() {
+        let mut templates = Templates::default();
+        templates.load(TEMPLATE).unwrap();
+        templates
+            .load("struct table {\n data: u8[100000000]\n entries: entry[3]\n}")
+            .unwrap();
+        let table = templates.get("table").unwrap();
+
+        let offset = |name| templates.field(table, name).map(|field| field.offset);
+
+        assert_eq!(offset("data[99999999]"), Some(99_999_999));
+        assert_eq!(offset("entries[0].head.magic"), Some(100_000_000));
+        assert_eq!(offset("entries[2].head.sizes[1]"), Some(100_000_037));
+        assert_eq!(offset("entries[2].flags"), Some(100_000_038));
+
+        let invalid = [
+            "data",
+            "data[100000000]",
+            "data[01]",
+            "data[+1]",
+            "entries",
+            "entries[0]",
+            "entries.flags",
+            "entries[3].flags",
+            "entries[0].head",
+            "entries[0].flags.x",
+            "entries[0].head._",
+            "entries[0].head.magic[0]",
+            "missing",
+        ];
+        for name in invalid {
+            assert!(offset(name).is_none(), "'{name}' is not None");
+        }
+    }
+}
@@ -0,0 +1,328 @@
+//! Fixed-width numeric types that can be read from and written to a file.
+
+use std::fmt;
+
+use crate::strings;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Endian {
+    Little,
+    Big,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    Unsigned,
+    Signed,
+    Float,
+}
+
+/// A numeric type such as `u32le` or `f64be`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValueType {
+    pub kind: Kind,
+    /// Width in bytes.
+    pub width: usize,
+    pub endian: Endian,
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            Kind::Unsigned => 'u',
+            Kind::Signed => 'i',
+            Kind::Float => 'f',
+        };
+        let endian = match (self.width, self.endian) {
+            (1, _) => "",
+            (_, Endian::Little) => "le",
+            (_, Endian::Big) => "be",
+        };
+
+        write!(f, "{kind}{}{endian}", self.width * 8)
+    }
+}
+
+impl ValueType {
+    /// Parses a type name. Types wider than a byte must have an `le` or `be`
+    /// suffix.
+    pub fn parse(name: &[u8]) -> Option<ValueType> {
+        let name = name.to_ascii_lowercase();
+
+        let (name, endian) = match name.as_slice() {
+            [name @ .., b'l', b'e'] => (name, Some(Endian::Little)),
+            [name @ .., b'b', b'e'] => (name, Some(Endian::Big)),
+            name => (name, None),
+        };
+
+        let (kind, bits) = match name {
+            [b'u', bits @ ..] => (Kind::Unsigned, bits),
+            [b'i', bits @ ..] => (Kind::Signed, bits),
+            [b'f', bits @ ..] => (Kind::Float, bits),
+            _ => return None,
+        };
+
+        let width = match (kind, bits) {
+            (Kind::Unsigned | Kind::Signed, b"8") => 1,
+            (Kind::Unsigned | Kind::Signed, b"16") => 2,
+            (Kind::Unsigned | Kind::Signed, b"32") => 4,
+            (Kind::Unsigned | Kind::Signed, b"64") => 8,
+            (Kind::Float, b"32") => 4,
+            (Kind::Float, b"64") => 8,
+            _ => return None,
+        };
+
+        let endian = match endian {
+            Some(endian) => endian,
+            None if width == 1 => Endian::Little,
+            None => return None,
+        };
+
+        Some(ValueType {
+            kind,
+            width,
+            endian,
+        })
+    }
+
+    /// Returns `bytes` as an unsigned integer in native order. `bytes` must be
+    /// `self.width` long.
+    pub fn raw(&self, bytes: &[u8]) -> u64 {
+        let mut buf = [0u8; 8];
+        match self.endian {
+            Endian::Little => {
+                buf[..self.width].copy_from_slice(bytes);
+                u64::from_le_bytes(buf)
+            }
+            Endian::Big => {
+                buf[8 - self.width..].copy_from_slice(bytes);
+                u64::from_be_bytes(buf)
+            }
+        }
+    }
+
+    /// Formats `bytes` as a value of this type in decimal. `bytes` must be
+    /// `self.width` long.
+    pub fn decimal(&self, bytes: &[u8]) -> String {
+        let raw = self.raw(bytes);
+
+        match (self.kind, self.width) {
+            (Kind::Unsigned, _) => raw.to_string(),
+            (Kind::Signed, _) => {
+                // Sign-extend.
+                let shift = 64 - self.width * 8;
+                ((raw << shift) as i64 >> shift).to_string()
+            }
+            // `Debug` switches to scientific notation for extreme exponents.
+            (Kind::Float, 4) => format!("{:?}", f32::from_bits(raw as u32)),
+            (Kind::Float, _) => format!("{:?}", f64::from_bits(raw)),
+        }
+    }
+
+    /// Formats `bytes` as a value of this type in decimal, followed by its
+    /// bits in hex. `bytes` must be `self.width` long.
+    pub fn format(&self, bytes: &[u8]) -> String {
+        format!(
+            "{} (0x{:0width$x})",
+            self.decimal(bytes),
+            self.raw(bytes),
+            width = self.width * 2
+        )
+    }
+
+    /// Encodes `text` as a value of this type. Integers may be given in hex
+    /// with a `0x` prefix.
+    pub fn encode(&self, text: &[u8]) -> Result<Vec<u8>, &'static str> {
+        let bits = self.width * 8;
+
+        let raw = match self.kind {
+            Kind::Unsigned => {
+                let num = parse_integer(text).ok_or(strings::INVALID_VALUE_ARG)?;
+                if bits < 64 && num >> bits != 0 {
+                    return Err(strings::VALUE_OUT_OF_RANGE);
+                }
+                num
+            }
+            Kind::Signed => {
+                let num = parse_signed_integer(text).ok_or(strings::INVALID_VALUE_ARG)?;
+                // Must survive truncation and sign-extension.
+                let shift = 64 - bits;
+                if (num << shift) >> shift != num {
+                    return Err(strings::VALUE_OUT_OF_RANGE);
+                }
+                num as u64
+            }
+            Kind::Float => {
+                let text = str::from_utf8(text).map_err(|_| strings::INVALID_VALUE_ARG)?;
+                match self.width {
+                    4 => text
+                        .parse::<f32>()
+                        .map_err(|_| strings::INVALID_VALUE_ARG)?
+                        .to_bits() as u64,
+                    _ => text
+                        .parse::<f64>()
+                        .map_err(|_| strings::INVALID_VALUE_ARG)?
+                        .to_bits(),
+                }
+            }
+        };
+
+        let bytes = match self.endian {
+            Endian::Little => raw.to_le_bytes()[..self.width].to_vec(),
+            Endian::Big => raw.to_be_bytes()[8 - self.width..].to_vec(),
+        };
+
+        Ok(bytes)
+    }
+}
+
+/// Parses an integer like `parse_integer`, with an optional leading `-`.
+pub fn parse_signed_integer(text: &[u8]) -> Option<i64> {
+    match text {
+        [b'-', magnitude @ ..] => 0i64.checked_sub_unsigned(parse_integer(magnitude)?),
+        magnitude => i64::try_from(parse_integer(magnitude)?).ok(),
+    }
+}
+
+/// Parses an unsigned integer in decimal, or in hex with a `0x` prefix.
+pub fn parse_integer(text: &[u8]) -> Option<u64> {
+    let (digits, radix) = match text {
+        [b'0', b'x' | b'X', digits @ ..] => (digits, 16),
+        digits => (digits, 10),
+    };
+
+    // `from_str_radix` accepts a leading sign, but a literal shouldn't.
+    if !digits.first()?.is_ascii_alphanumeric() {
+        return None;
+    }
+
+    // TODO: use u64::from_ascii_radix once stable
+    u64::from_str_radix(str::from_utf8(digits).ok()?, radix).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn type_names_are_parsed_correctly() {
+        let u8_type = ValueType::parse(b"u8").unwrap();
+        let i16_type = ValueType::parse(b"I16BE").unwrap();
+        let f64_type = ValueType::parse(b"f64le").unwrap();
+
+        assert_eq!(
+            u8_type,
+            ValueType {
+                kind: Kind::Unsigned,
+                width: 1,
+                endian: Endian::Little
+            }
+        );
+        assert_eq!(
+            i16_type,
+            ValueType {
+                kind: Kind::Signed,
+                width: 2,
+                endian: Endian::Big
+            }
+        );
+        assert_eq!(
+            f64_type,
+            ValueType {
+                kind: Kind::Float,
+                width: 8,
+                endian: Endian::Little
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_type_names_return_none() {
+        let inputs: &[&[u8]] = &[b"", b"u", b"u32", b"u24le", b"f16le", b"f8", b"x8", b"le"];
+
+        for input in inputs {
+            assert_eq!(
+                ValueType::parse(input),
+                None,
+                "'{}' is not None",
+                String::from_utf8_lossy(input)
+            );
+        }
+    }
+
+    #[test]
+    fn values_are_formatted_correctly() {
+        let inputs: &[(&[u8], &[u8], &str)] = &[
+            (b"u8", &[0xff], "255 (0xff)"),
+            (b"i8", &[0xff], "-1 (0xff)"),
+            (b"u16le", &[0x34, 0x12], "4660 (0x1234)"),
+            (b"u16be", &[0x34, 0x12], "13330 (0x3412)"),
+            (b"i32le", &[0xfe, 0xff, 0xff, 0xff], "-2 (0xfffffffe)"),
+            (b"f32be", &[0xbf, 0xc0, 0x00, 0x00], "-1.5 (0xbfc00000)"),
+            (
+                b"u64be",
+                &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+                "18446744073709551615 (0xffffffffffffffff)",
+            ),
+        ];
+
+        for (name, bytes, formatted) in inputs {
+            let value_type = ValueType::parse(name).unwrap();
+            assert_eq!(value_type.format(bytes), *formatted);
+        }
+    }
+
+    #[test]
+    fn values_are_encoded_correctly() {
+        let inputs: &[(&[u8], &[u8], &[u8])] = &[
+            (b"u8", b"255", &[0xff]),
+            (b"i8", b"-128", &[0x80]),
+            (b"u32le", b"0xdeadbeef", &[0xef, 0xbe, 0xad, 0xde]),
+            (b"u32be", b"0XDEADBEEF", &[0xde, 0xad, 0xbe, 0xef]),
+            (b"i16le", b"-2", &[0xfe, 0xff]),
+            (b"i16be", b"-0x2", &[0xff, 0xfe]),
+            (b"f32be", b"-1.5", &[0xbf, 0xc0, 0x00, 0x00]),
+            (
+                b"u64le",
+                b"18446744073709551615",
+                &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+            ),
+            (
+                b"i64be",
+                b"-9223372036854775808",
+                &[0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            ),
+        ];
+
+        for (name, text, bytes) in inputs {
+            let value_type = ValueType::parse(name).unwrap();
+            assert_eq!(value_type.encode(text).unwrap(), *bytes);
+        }
+    }
+
+    #[test]
+    fn invalid_values_return_err() {
+        let inputs: &[(&[u8], &[u8])] = &[
+            (b"u8", b"256"),
+            (b"u8", b"-1"),
+            (b"u8", b"+1"),
+            (b"i8", b"128"),
+            (b"i8", b"-129"),
+            (b"i8", b"--1"),
+            (b"u16le", b"0x"),
+            (b"u16le", b"0x-1"),
+            (b"u16le", b"1.0"),
+            (b"f32le", b"x"),
+        ];
+
+        for (name, text) in inputs {
+            let value_type = ValueType::parse(name).unwrap();
+            assert!(
+                value_type.encode(text).is_err(),
+                "'{}' is not Err for {}",
+                String::from_utf8_lossy(text),
+                String::from_utf8_lossy(name)
+            );
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Variable-length integer encodings.
+
+use crate::repl::value::{parse_integer, parse_signed_integer};
+
+/// Longest encoding of a 64-bit integer.
+pub const MAX_LEN: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    /// Unsigned LEB128, which is also the protobuf varint.
+    Uleb128,
+    Sleb128,
+    /// Protobuf `sint` varint: ZigZag-mapped, then unsigned LEB128.
+    Zigzag,
+}
+
+impl Format {
+    pub fn parse(name: &[u8]) -> Option<Format> {
+        match name.to_ascii_lowercase().as_slice() {
+            b"uleb128" | b"varint" => Some(Format::Uleb128),
+            b"sleb128" => Some(Format::Sleb128),
+            b"zigzag" => Some(Format::Zigzag),
+            _ => None,
+        }
+    }
+
+    /// Decodes and formats the integer at the start of `bytes`. Returns the
+    /// text and the number of bytes it took.
+    pub fn format(&self, bytes: &[u8]) -> Option<(String, usize)> {
+        match self {
+            Format::Uleb128 => decode_uleb128(bytes).map(|(n, len)| (n.to_string(), len)),
+            Format::Sleb128 => decode_sleb128(bytes).map(|(n, len)| (n.to_string(), len)),
+            Format::Zigzag => decode_uleb128(bytes).map(|(n, len)| (unzigzag(n).to_string(), len)),
+        }
+    }
+
+    /// Encodes the integer in `text`, which may be in hex with a `0x` prefix.
+    pub fn encode(&self, text: &[u8]) -> Option<Vec<u8>> {
+        match self {
+            Format::Uleb128 => parse_integer(text).map(encode_uleb128),
+            Format::Sleb128 => parse_signed_integer(text).map(encode_sleb128),
+            Format::Zigzag => parse_signed_integer(text).map(|n| encode_uleb128(zigzag(n))),
+        }
+    }
+}
+
+pub fn encode_uleb128(mut value: u64) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(MAX_LEN);
+
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return bytes;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+pub fn encode_sleb128(mut value: i64) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(MAX_LEN);
+
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        // Done once the rest is all sign bits, and the sign bit of this byte
+        // agrees.
+        if (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0) {
+            bytes.push(byte);
+            return bytes;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+/// Decodes an unsigned LEB128 integer from the start of `bytes`. Returns the
+/// value and the number of bytes it took, or `None` if it is unterminated or
+/// too large.
+pub fn decode_uleb128(bytes: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+
+    for (i, byte) in bytes.iter().enumerate() {
+        let shift = 7 * i as u32;
+        let bits = (byte & 0x7f) as u64;
+        if shift >= 64 || (bits << shift) >> shift != bits {
+            return None;
+        }
+        value |= bits << shift;
+
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+
+    None
+}
+
+/// Decodes a signed LEB128 integer from the start of `bytes`. Returns the
+/// value and the number of bytes it took, or `None` if it is unterminated or
+/// too large.
+pub fn decode_sleb128(bytes: &[u8]) -> Option<(i64, usize)> {
+    let mut value = 0i64;
+
+    for (i, byte) in bytes.iter().enumerate() {
+        let shift = 7 * i as u32;
+        // The last byte holds bit 63, and the rest of it must repeat that
+        // bit as sign extension.
+        if shift >= 64 || (shift == 63 && !matches!(byte & 0x7f, 0x00 | 0x7f)) {
+            return None;
+        }
+        value |= ((byte & 0x7f) as i64) << shift;
+
+        if byte & 0x80 == 0 {
+            // Sign-extend.
+            let bits = shift + 7;
+            if bits < 64 {
+                value = value << (64 - bits) >> (64 - bits);
+            }
+            return Some((value, i + 1));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leb128_is_decoded_correctly() {
+        assert_eq!(decode_uleb128(&[0x00]), Some((0, 1)));
+        assert_eq!(decode_uleb128(&[0xe5, 0x8e, 0x26, 0xff]), Some((624485, 3)));
+        assert_eq!(decode_sleb128(&[0x7f]), Some((-1, 1)));
+        assert_eq!(decode_sleb128(&[0xc0, 0xbb, 0x78]), Some((-123456, 3)));
+        assert_eq!(decode_sleb128(&[0x3f]), Some((63, 1)));
+        for value in [i64::MIN, i64::MAX] {
+            assert_eq!(
+                decode_sleb128(&encode_sleb128(value)),
+                Some((value, MAX_LEN))
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_leb128_returns_none() {
+        assert_eq!(decode_uleb128(&[]), None);
+        assert_eq!(decode_uleb128(&[0x80, 0x80]), None);
+        assert_eq!(decode_uleb128(&[0xff; 10]), None);
+        assert_eq!(decode_sleb128(&[0xff; 11]), None);
+        assert_eq!(
+            decode_sleb128(&[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x7e]),
+            None
+        );
+        assert_eq!(
+            decode_sleb128(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]),
+            None
+        );
+    }
+
+    #[test]
+    fn leb128_is_encoded_correctly() {
+        assert_eq!(encode_uleb128(0), [0x00]);
+        assert_eq!(encode_uleb128(624485), [0xe5, 0x8e, 0x26]);
+        assert_eq!(encode_uleb128(u64::MAX).len(), MAX_LEN);
+        assert_eq!(encode_sleb128(-1), [0x7f]);
+        assert_eq!(encode_sleb128(63), [0x3f]);
+        assert_eq!(encode_sleb128(64), [0xc0, 0x00]);
+        assert_eq!(encode_sleb128(-123456), [0xc0, 0xbb, 0x78]);
+        assert_eq!(encode_sleb128(i64::MIN).len(), MAX_LEN);
+    }
+
+    #[test]
+    fn zigzag_round_trips() {
+        let inputs: &[(&[u8], &[u8])] = &[
+            (b"0", &[0x00]),
+            (b"-1", &[0x01]),
+            (b"1", &[0x02]),
+            (b"-64", &[0x7f]),
+            (b"64", &[0x80, 0x01]),
+        ];
+
+        for (text, bytes) in inputs {
+            assert_eq!(Format::Zigzag.encode(text).unwrap(), *bytes);
+            assert_eq!(
+                Format::Zigzag.format(bytes).unwrap(),
+                (str::from_utf8(text).unwrap().to_string(), bytes.len())
+            );
+        }
+
+        let extremes = [i64::MIN, i64::MAX];
+        for n in extremes {
+            assert_eq!(unzigzag(zigzag(n)), n);
+        }
+    }
+}
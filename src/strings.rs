@@ -15,11 +15,81 @@ pub static HELP: LazyLock<String> = LazyLock::new(|| {
     )
 });
 
+pub const OPTIONS: &str = "Options:
+    -s, --size       Show the file size in the prompt.
+    --color <when>   Colour hex dumps: auto, always or never. Default: auto,
+                     which colours them when output is a terminal. Can also
+                     be written `--color=<when>`.
+    --               End options. Later arguments are taken as the file, even
+                     if they start with `-`.";
+
 pub const NOT_SEEKABLE_USE_DOT: &str = "File not seekable. Use `.` in seek argument.";
 pub const ENTER_HELP_FOR_USAGE: &str = "Enter \"help\" for usage.";
 pub const UNRECOGNIZED_COMMAND: &str = "Unrecognized command.";
 pub const MISSING_SEEK_ARG: &str = "Missing seek argument.";
+pub const MISSING_RANGE_ARG: &str = "Missing range argument.";
+pub const MISSING_SIZE_ARG: &str = "Missing size argument.";
+pub const MISSING_FORMAT_ARG: &str = "Missing format argument.";
+pub const MISSING_ENCODING_ARG: &str = "Missing encoding argument.";
+pub const MISSING_TYPE_ARG: &str = "Missing type argument.";
+pub const MISSING_VALUE_ARG: &str = "Missing value argument.";
+pub const MISSING_COUNT_ARG: &str = "Missing count argument.";
+pub const MISSING_STRUCT_ARG: &str = "Missing structure argument.";
+pub const MISSING_FIELD_ARG: &str = "Missing field argument. Use `<struct>.<field>`.";
+pub const MISSING_PATH_ARG: &str = "Missing path argument.";
+pub const MISSING_SUBCOMMAND: &str = "Missing subcommand.";
+pub const MISSING_MASK_ARG: &str = "Missing mask argument.";
+pub const MISSING_OPERATION_ARG: &str = "Missing operation argument.";
+pub const MISSING_KEY_ARG: &str = "Missing key argument.";
+pub const UNEXPECTED_KEY_ARG: &str = "Unexpected key argument.";
+pub const UNEXPECTED_ARG: &str = "Unexpected argument.";
+pub const MISSING_WIDTH_ARG: &str = "Missing width argument.";
+pub const MISSING_PATTERN_ARG: &str = "Missing pattern argument.";
+pub const MISSING_BYTES_ARG: &str = "Missing bytes argument.";
 pub const INVALID_DIGIT_IN_COUNT_ARG: &str = "Invalid digit in count argument.";
 pub const INVALID_BYTE_ARG: &str = "Invalid byte argument.";
 pub const INVALID_DIGIT_IN_SEEK_ARG: &str = "Invalid digit in seek argument.";
 pub const INVALID_SEEK_ARG: &str = "Invalid seek argument.";
+pub const INVALID_SUBCOMMAND: &str = "Invalid subcommand.";
+pub const INVALID_TYPE_ARG: &str = "Invalid type argument.";
+pub const INVALID_FORMAT_ARG: &str = "Invalid format argument.";
+pub const INVALID_ENCODING_ARG: &str = "Invalid encoding argument.";
+pub const INVALID_ORDER_ARG: &str = "Invalid bit order argument. Use `msb` or `lsb`.";
+pub const INVALID_OPERATION_ARG: &str = "Invalid operation argument.";
+pub const INVALID_WIDTH_ARG: &str = "Invalid width argument. Use 2, 4 or 8.";
+pub const INVALID_PREFIX_TYPE_ARG: &str = "Invalid type argument. Use an unsigned integer type.";
+pub const INVALID_VALUE_ARG: &str = "Invalid value argument.";
+pub const VALUE_OUT_OF_RANGE: &str = "Value out of range for type.";
+pub const NOT_SEEKABLE: &str = "File not seekable.";
+pub const NOT_SEEKABLE_GIVE_COUNT: &str = "File not seekable. Give a count in range argument.";
+pub const NOT_REGULAR_FILE: &str = "Not a regular file.";
+pub const SIZE_OUT_OF_RANGE: &str = "Size out of range.";
+pub const UNEXPECTED_EOF: &str = "Unexpected end of file.";
+pub const LENGTH_TOO_LARGE: &str = "Length too large.";
+pub const UNKNOWN_STRUCT: &str = "Unknown structure. Use `template load` to load it.";
+pub const UNKNOWN_FIELD: &str = "Unknown field.";
+pub const TEXT_TOO_LONG: &str = "Text too long for field.";
+pub const TEMPLATE_EXPECTED_STRUCT: &str = "Expected `struct <name> {`.";
+pub const TEMPLATE_EXPECTED_FIELD: &str = "Expected `<name>: <type>` or `}`.";
+pub const TEMPLATE_INVALID_NAME: &str = "Invalid name.";
+pub const TEMPLATE_INVALID_COUNT: &str = "Invalid array count.";
+pub const TEMPLATE_UNKNOWN_TYPE: &str = "Unknown type.";
+pub const TEMPLATE_DUPLICATE_FIELD: &str = "Duplicate field name.";
+pub const TEMPLATE_TOO_LARGE: &str = "Structure too large.";
+pub const TEMPLATE_UNCLOSED_STRUCT: &str = "Missing `}` at end of file.";
+pub const MALFORMED_VARINT: &str = "Malformed variable-length integer.";
+pub const RANGE_NOT_MULTIPLE_OF_WIDTH: &str = "Range length is not a multiple of the width.";
+pub const UNKNOWN_SETTING: &str = "Unknown setting. Enter `set` to list them.";
+pub const INVALID_SWITCH: &str = "Invalid value. Use `on` or `off`.";
+pub const INVALID_HEX_WIDTH: &str = "Invalid width. Use a number from 1 to 256.";
+pub const INVALID_HEX_GROUP: &str = "Invalid group size. Use 1, 2, 4 or 8.";
+pub const INVALID_HEX_OFFSET: &str = "Invalid offset format. Use `dec` or `hex`.";
+pub const INVALID_HEX_RADIX: &str = "Invalid radix. Use `hex`, `oct`, `dec` or `bin`.";
+pub const INVALID_HEX_WORDS: &str = "Invalid word order. Use `off`, `le` or `be`.";
+pub const INVALID_HEX_CHARSET: &str =
+    "Invalid charset. Use `ascii`, `utf-8`, `latin-1`, `cp437` or `ebcdic`.";
+pub const ADDRESS_OUT_OF_RANGE: &str = "Address out of range.";
+pub const INVALID_ENCODED_DATA: &str = "Data is not valid in the given encoding.";
+pub const INVALID_RECORD: &str = "Invalid record.";
+pub const UNKNOWN_RECORD_TYPE: &str = "Unknown record type.";
+pub const RECORD_CHECKSUM_MISMATCH: &str = "Record checksum mismatch.";